@@ -1,52 +1,161 @@
+mod cursor;
+mod diagnostics;
 mod file;
 mod highlighter;
+mod history;
+mod merge;
+mod options;
+mod params;
+mod profile;
+mod prompt;
+mod recent;
+mod search;
+mod source_map;
+mod settings;
+mod tab;
 mod validation;
 
-use crate::editor::highlighter::Highlighter;
+pub use crate::editor::options::Options;
+pub use crate::editor::settings::Settings;
+
+use crate::editor::diagnostics::Diagnostic;
+use crate::editor::highlighter::{Highlighter, Lines};
+use crate::editor::prompt::Pending;
+use crate::editor::recent::Recent;
+use crate::editor::search::Search;
+use crate::editor::tab::Tab;
 use crate::preferences::Preferences;
-use crate::theme::{ContainerClass, TextClass, Theme};
-use crate::{FragmentShader, JETBRAINS_MONO, preferences};
-use iced::alignment::Horizontal;
+use crate::toast::Toast;
+use crate::viewer::{self, Image, PipelineError};
+use crate::theme::{ButtonClass, ContainerClass, Theme};
+use crate::{FragmentShader, Shader, preferences};
+use iced::advanced::text::Paragraph;
+use iced::advanced::{self, graphics};
+use iced::alignment::{Horizontal, Vertical};
 use iced::keyboard::key::Named;
 use iced::widget::text_editor::{Action, Binding, KeyPress};
-use iced::widget::{button, checkbox, column, container, row, scrollable, text, text_editor, tooltip, Text};
-use iced::{Alignment, Length, Task, keyboard, Font};
+use iced::widget::text::LineHeight;
+use iced::widget::{button, checkbox, column, container, mouse_area, opaque, pick_list, responsive, row, scrollable, stack, text, text_editor, text_input, tooltip};
+use iced::{Alignment, Font, Length, Padding, Point, Size, Subscription, Task, keyboard, time};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
 use std::ops::Range;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use lucide_icons::Icon;
 
 type Element<'a, Message> = iced::Element<'a, Message, Theme>;
 
+/// How many columns apart tab stops are. iced leaves cosmic-text's default in place, whatever
+/// the indent size is.
+const TAB_WIDTH: usize = 8;
+
 #[derive(Clone, Debug)]
 pub enum Message {
-    Init(Result<(Preferences, Arc<FragmentShader>), preferences::Error>),
+    Init(Result<preferences::Session, preferences::Error>),
     Action(Action),
     Validate,
-    Validated(Result<Arc<FragmentShader>, validation::Error>),
+    Validated(tab::Id, usize, validation::Validated),
     AutoValidate(bool),
     New,
     Open,
     Opened(Result<(PathBuf, Arc<FragmentShader>), file::Error>),
+    ToggleRecent,
+    ToggleSettings,
+    ChangeSetting(settings::Change),
+    ToggleOptions,
+    ChangeOptions(options::Change),
+    /// Clears the buffers of a multi-pass shader.
+    ResetBuffers,
+    /// Asks for an image for a channel to sample.
+    PickChannel(usize),
+    ChannelPicked(usize, Option<PathBuf>),
+    ImageLoaded(PathBuf, Result<Arc<Image>, file::Error>),
+    ToggleParams,
+    /// Sets a shader param to a new value.
+    ChangeParam(String, Vec<f32>),
+    ResetParams,
+    Pad(params::Pad),
+    RecentChecked(Vec<PathBuf>),
+    OpenFile(PathBuf),
+    RemoveRecent(PathBuf),
+    PruneRecent,
     Save,
-    Saved(Result<PathBuf, file::Error>),
+    SaveAs,
+    SaveCopy,
+    Saved(tab::Id, Result<(PathBuf, Arc<FragmentShader>), file::Error>),
+    SavedCopy(tab::Id, Result<(PathBuf, Arc<FragmentShader>), file::Error>),
+    SaveTo(tab::Id, PathBuf),
+    SaveCopyTo(tab::Id, PathBuf),
+    SelectTab(tab::Id),
+    CloseTab(tab::Id),
+    Exit,
+    Confirm(prompt::Choice),
+    CheckFiles,
+    FileModified(tab::Id, Result<SystemTime, file::Error>),
+    FileChanged(tab::Id, SystemTime, Result<(PathBuf, Arc<FragmentShader>), file::Error>),
     Undo,
     Redo,
     Search,
+    CloseSearch,
+    SearchChanged(String),
+    ReplacementChanged(String),
+    ToggleSearchOption(search::Toggle),
+    FindNext,
+    FindPrevious,
+    Replace,
+    ReplaceAll,
     Indent,
+    /// Selects a span of the active buffer, like a diagnostic's location.
+    JumpTo(Range<usize>),
+    /// The mouse moved over the editor, which is `Size` big.
+    Hovered(Point, Size),
+    Unhovered,
+    /// The viewer found out what its device supports.
+    DeviceReported(viewer::Device),
+    /// The viewer couldn't build a pipeline for a shader and kept showing the previous one.
+    PipelineFailed(PipelineError),
+    SavePreferences,
     PreferencesSaved(Result<(), preferences::Error>),
+    /// Shows a toast, for updates that have more than one to show.
+    Notify(Box<Toast>),
 }
 
 pub enum Event {
     None,
-    UpdatePipeline(Arc<FragmentShader>),
+    /// A new shader for the viewer, along with its params.
+    UpdatePipeline(Shader, Vec<u8>),
+    /// New values for the params of the viewer's shader.
+    UpdateParams(Vec<u8>),
+    ResetBuffers,
+    Notify(Toast),
+    Exit,
 }
 
 pub struct Editor {
-    content: text_editor::Content,
-    theme: iced::highlighter::Theme,
-    shader_path: Option<PathBuf>,
-    validation_status: validation::Status,
+    tabs: Vec<Tab>,
+    active: usize,
+    search: Search,
+    recent: Recent,
+    pending: Option<Pending>,
+    is_saving_pending: bool,
+    settings: Settings,
+    is_settings_open: bool,
+    is_options_open: bool,
+    is_params_open: bool,
+    /// Follows the mouse over the params' pads.
+    drag: params::Drag,
+    /// Where the mouse is over the editor.
+    hover: Option<Point>,
+    /// The size of the editor when the mouse was last over it.
+    viewport: Size,
+    /// What the viewer's device supports, once it's known.
+    device: Option<viewer::Device>,
+    /// Options for files, including ones that aren't open.
+    files: BTreeMap<PathBuf, Options>,
+    /// Images channels sample, `None` while they load or if they couldn't be.
+    images: HashMap<PathBuf, Option<Arc<Image>>>,
     auto_validate: bool,
     is_loading: bool,
 }
@@ -54,12 +163,25 @@ pub struct Editor {
 impl Default for Editor {
     fn default() -> Self {
         Self {
-            content: text_editor::Content::with_text(include_str!(
-                "viewer/shaders/default_frag.wgsl"
-            )),
-            theme: iced::highlighter::Theme::Base16Mocha,
-            shader_path: None,
-            validation_status: validation::Status::default(),
+            tabs: vec![Tab::new(
+                None,
+                include_str!("viewer/shaders/default_frag.wgsl"),
+            )],
+            active: 0,
+            search: Search::default(),
+            recent: Recent::default(),
+            pending: None,
+            is_saving_pending: false,
+            settings: Settings::default(),
+            is_settings_open: false,
+            is_options_open: false,
+            is_params_open: false,
+            drag: params::Drag::default(),
+            hover: None,
+            viewport: Size::ZERO,
+            device: None,
+            files: BTreeMap::new(),
+            images: HashMap::new(),
             auto_validate: true,
             is_loading: true,
         }
@@ -75,6 +197,16 @@ impl Editor {
             keyboard::Key::Named(Named::Tab) => {
                 Some(Binding::Custom(Message::Indent))
             }
+            keyboard::Key::Character("s" | "S")
+                if keypress.modifiers.command() && keypress.modifiers.shift() =>
+            {
+                Some(Binding::Custom(Message::SaveAs))
+            }
+            keyboard::Key::Character("s" | "S")
+                if keypress.modifiers.command() && keypress.modifiers.alt() =>
+            {
+                Some(Binding::Custom(Message::SaveCopy))
+            }
             keyboard::Key::Character("s") if keypress.modifiers.command() => {
                 Some(Binding::Custom(Message::Save))
             }
@@ -87,48 +219,128 @@ impl Editor {
             keyboard::Key::Character("f") if keypress.modifiers.command() => {
                 Some(Binding::Custom(Message::Search))
             }
+            keyboard::Key::Character("w") if keypress.modifiers.command() => {
+                Some(Binding::Custom(Message::CloseTab(self.tab().id)))
+            }
+            keyboard::Key::Named(Named::Escape) if self.search.is_open => {
+                Some(Binding::Custom(Message::CloseSearch))
+            }
             _ => Binding::from_key_press(keypress),
         }
     }
 
     pub fn update(&mut self, update: Message) -> (Event, Task<Message>) {
+        let (id, cursor) = (self.tab().id, self.tab().content.cursor_position());
+        let result = self.perform(update);
+
+        // The editor scrolls to keep a cursor that moved in view, dragging a selection past its
+        // edge included, follow it
+        if self.tab().id == id && self.tab().content.cursor_position() != cursor {
+            let (line_height, height) = (self.line_height(), self.text_height());
+
+            self.tab_mut().follow_cursor(line_height, height);
+        }
+
+        result
+    }
+
+    fn perform(&mut self, update: Message) -> (Event, Task<Message>) {
         match update {
             Message::Init(result) => {
+                let mut event = Event::None;
+
                 let cmd = match result {
-                    Ok((prefs, shader)) => {
+                    Ok(preferences::Session {
+                        preferences: prefs,
+                        shaders,
+                        warnings,
+                        imported_from,
+                    }) => {
+                        let mut toasts = Vec::new();
+
+                        if !warnings.is_empty() {
+                            toasts.push(Toast::warning(
+                                "Some preferences couldn't be restored",
+                                warnings.join("\n"),
+                            ));
+                        }
+
+                        if let Some(path) = imported_from {
+                            toasts.push(Toast::warning(
+                                "Imported preferences",
+                                format!(
+                                    "Copied from {} to {}",
+                                    path.display(),
+                                    preferences::PATH.display()
+                                ),
+                            ));
+                        }
+
+                        // Only one toast fits in the event, send the rest on their own
+                        let mut toasts = toasts.into_iter();
+                        event = toasts.next().map_or(Event::None, Event::Notify);
+                        let notify = Task::batch(
+                            toasts.map(|toast| Task::done(Message::Notify(Box::new(toast)))),
+                        );
+
                         self.auto_validate = prefs.auto_validate;
-                        self.shader_path = prefs.last_shader_path;
-                        self.content = text_editor::Content::with_text(&shader);
-                        Task::perform(validation::validate(shader), Message::Validated)
+                        self.recent = Recent::new(prefs.recent_files);
+                        self.settings = prefs.editor;
+                        self.files = prefs.files;
+
+                        if !shaders.is_empty() {
+                            self.tabs = shaders
+                                .iter()
+                                .map(|(path, shader)| {
+                                    let mut tab = Tab::new(Some(path.clone()), shader);
+                                    tab.options = self.files.get(path).cloned().unwrap_or_default();
+                                    tab
+                                })
+                                .collect();
+                            self.active = prefs.active_tab.min(self.tabs.len() - 1);
+                        }
+
+                        self.search.find(&self.tab().text());
+
+                        Task::batch([self.validate_all(), self.load_images(), notify])
                     }
-                    Err(e) => {
-                        println!("Error loading prefs: {e:?}");
-                        Task::none()
+                    Err(error) => {
+                        // There's nothing to load on the first run
+                        if !matches!(error, preferences::Error::Io(io::ErrorKind::NotFound)) {
+                            event = Event::Notify(Toast::error("Couldn't load preferences", error));
+                        }
+
+                        self.validate_all()
                     }
                 };
 
                 self.is_loading = false;
-                return (Event::None, cmd);
+                return (event, cmd);
             }
             Message::Action(action) => {
                 // TODO: Fix not being able to use hotkeys while text editor is focused
-                let should_validate = action.is_edit() && self.auto_validate;
-                self.content.perform(action);
+                let (line_height, height) = (self.line_height(), self.text_height());
+                let tab = self.tab_mut();
 
-                if should_validate {
-                    return self.update(Message::Validate);
+                if let Action::Scroll { lines } = action {
+                    tab.scroll_by(lines, line_height, height);
+                }
+
+                if let Action::Edit(edit) = action {
+                    let is_valid = tab.is_valid();
+                    tab.history.perform(&mut tab.content, edit, is_valid);
+
+                    return self.changed();
+                } else {
+                    tab.content.perform(action);
                 }
             }
             Message::New => {
                 let empty_shader = include_str!("viewer/shaders/empty_frag.wgsl");
 
-                self.shader_path = None;
-                self.content = text_editor::Content::with_text(empty_shader);
+                self.open_tab(Tab::new(None, empty_shader));
 
-                return (
-                    Event::UpdatePipeline(Arc::new(empty_shader.to_string())),
-                    Task::none(),
-                );
+                return (self.pipeline(), Task::none());
             }
             Message::Open => {
                 let cmd = if self.is_loading {
@@ -141,59 +353,534 @@ impl Editor {
                 return (Event::None, cmd);
             }
             Message::Opened(result) => {
-                let cmds = if let Ok((path, shader)) = result {
-                    self.shader_path = Some(path);
-                    self.content = text_editor::Content::with_text(&shader);
+                self.is_loading = false;
+
+                let (path, shader) = match result {
+                    Ok(opened) => opened,
+                    Err(error) if error.is_cancelled() => return (Event::None, Task::none()),
+                    Err(error) => return (Event::Notify(open_failed(error)), Task::none()),
+                };
+
+                // Switch to the file if it's already open instead of opening it twice
+                if let Some(index) = self
+                    .tabs
+                    .iter()
+                    .position(|tab| tab.shader_path.as_ref() == Some(&path))
+                {
+                    self.recent.push(path);
+                    return self.update(Message::SelectTab(self.tabs[index].id));
+                }
+
+                self.recent.push(path.clone());
+                self.open_tab(Tab::new(Some(path), &shader));
+
+                let target = self.target();
 
+                return (
+                    Event::None,
                     Task::batch(vec![
                         self.save_prefs(),
-                        Task::perform(validation::validate(shader), Message::Validated),
-                    ])
+                        validate(self.tab_mut(), Duration::ZERO, target),
+                        self.load_images(),
+                    ]),
+                );
+            }
+            Message::ToggleRecent => {
+                self.recent.is_open = !self.recent.is_open;
+                self.is_settings_open = false;
+                self.is_options_open = false;
+                self.is_params_open = false;
+
+                if self.recent.is_open {
+                    return (
+                        Event::None,
+                        Task::perform(
+                            file::missing(self.recent.files().to_vec()),
+                            Message::RecentChecked,
+                        ),
+                    );
+                }
+            }
+            Message::ToggleSettings => {
+                self.is_settings_open = !self.is_settings_open;
+                self.recent.is_open = false;
+                self.is_options_open = false;
+                self.is_params_open = false;
+            }
+            Message::ResetBuffers => {
+                return (Event::ResetBuffers, Task::none());
+            }
+            Message::ToggleOptions => {
+                self.is_options_open = !self.is_options_open;
+                self.recent.is_open = false;
+                self.is_settings_open = false;
+                self.is_params_open = false;
+            }
+            Message::ToggleParams => {
+                self.is_params_open = !self.is_params_open;
+                self.recent.is_open = false;
+                self.is_settings_open = false;
+                self.is_options_open = false;
+            }
+            Message::ChangeParam(name, value) => {
+                self.tab_mut().options.params.insert(name, value);
+
+                return (self.params(), Task::none());
+            }
+            Message::ResetParams => {
+                self.tab_mut().options.params.clear();
+
+                return (self.params(), self.save_prefs());
+            }
+            Message::Pad(event) => {
+                let is_released = matches!(event, params::Pad::Released);
+
+                if let Some((name, value)) = self.drag.update(event) {
+                    return self.update(Message::ChangeParam(name, value));
+                }
+
+                if is_released {
+                    return (Event::None, self.save_prefs());
+                }
+            }
+            Message::ChangeSetting(change) => {
+                let profile = self.settings.target_profile;
+                self.settings.apply(change);
+
+                if self.settings.target_profile != profile {
+                    return (
+                        Event::None,
+                        Task::batch([self.save_prefs(), self.validate_all()]),
+                    );
+                }
+
+                return (Event::None, self.save_prefs());
+            }
+            Message::PipelineFailed(error) => {
+                let tab = self.tabs.iter_mut().find(|tab| {
+                    tab.last_valid_shader
+                        .as_ref()
+                        .is_some_and(|shader| Arc::ptr_eq(&shader.source, &error.shader.source))
+                });
+
+                let Some(tab) = tab else {
+                    return (Event::None, Task::none());
+                };
+
+                // Don't send the viewer the rejected shader again when coming back to the tab
+                tab.last_valid_shader = None;
+
+                // Edits since then will be revalidated and replace the shader anyway
+                if matches!(tab.validation_status, validation::Status::Validated) {
+                    tab.validation_status =
+                        validation::Status::Invalid(validation::Error::pipeline(&error.message));
+                }
+            }
+            Message::DeviceReported(device) => {
+                self.device = Some(device);
+
+                if self.settings.target_profile == profile::Profile::Device {
+                    return (Event::None, self.validate_all());
+                }
+            }
+            Message::RecentChecked(missing) => {
+                self.recent.set_missing(missing);
+            }
+            Message::OpenFile(path) => {
+                self.recent.is_open = false;
+                self.is_loading = true;
+
+                return (
+                    Event::None,
+                    Task::perform(file::load(path), Message::Opened),
+                );
+            }
+            Message::RemoveRecent(path) => {
+                self.recent.remove(&path);
+
+                return (Event::None, self.save_prefs());
+            }
+            Message::PruneRecent => {
+                self.recent.prune();
+
+                return (Event::None, self.save_prefs());
+            }
+            Message::SelectTab(id) => {
+                if let Some(index) = self.tabs.iter().position(|tab| tab.id == id) {
+                    self.active = index;
+                    self.search.find(&self.tab().text());
+
+                    return (self.pipeline(), self.save_prefs());
+                }
+            }
+            Message::CloseTab(id) => {
+                if self.tab_by_id(id).is_some_and(|tab| tab.is_dirty) {
+                    self.pending = Some(Pending::CloseTab(id));
                 } else {
-                    Task::none()
+                    return self.close_tab(id);
+                }
+            }
+            Message::Exit => {
+                if self.is_dirty() {
+                    self.pending = Some(Pending::Exit);
+                } else {
+                    return (Event::Exit, Task::none());
+                }
+            }
+            Message::Confirm(choice) => {
+                let Some(pending) = self.pending.clone() else {
+                    return (Event::None, Task::none());
                 };
 
-                // TODO: Loading error msg
-                self.is_loading = false;
+                if let Pending::Changed(id, contents) = pending {
+                    self.pending = None;
+
+                    let Some(tab) = self.tab_by_id(id) else {
+                        return (Event::None, Task::none());
+                    };
+
+                    match choice {
+                        prompt::Choice::Merge => {
+                            let conflicts = tab.merge(&contents);
+
+                            if conflicts > 0 {
+                                let toast = Toast::warning(
+                                    format!("Conflicts merging {}", tab.title()),
+                                    format!("{conflicts} conflict(s) are marked in the editor"),
+                                );
+                                let (_, task) = self.reloaded(id);
 
-                return (Event::None, cmds);
+                                return (Event::Notify(toast), task);
+                            }
+                        }
+                        prompt::Choice::Discard => tab.reload(&contents),
+                        _ => {
+                            tab.keep(&contents);
+                            return (Event::None, Task::none());
+                        }
+                    }
+
+                    return self.reloaded(id);
+                }
+
+                match choice {
+                    prompt::Choice::Save => {
+                        self.is_saving_pending = true;
+
+                        // Carry on once `Saved` comes back for every tab involved
+                        let saves = self
+                            .tabs
+                            .iter()
+                            .filter(|tab| match pending {
+                                Pending::CloseTab(id) => tab.id == id,
+                                _ => tab.is_dirty,
+                            })
+                            .map(save)
+                            .collect::<Vec<_>>();
+
+                        return (Event::None, Task::batch(saves));
+                    }
+                    prompt::Choice::Discard => {
+                        self.pending = None;
+
+                        return match pending {
+                            Pending::CloseTab(id) => self.close_tab(id),
+                            _ => (Event::Exit, Task::none()),
+                        };
+                    }
+                    _ => {
+                        self.pending = None;
+                    }
+                }
+            }
+            Message::CheckFiles => {
+                let checks = self.tabs.iter().filter_map(|tab| {
+                    let id = tab.id;
+                    let path = tab.shader_path.clone()?;
+
+                    Some(Task::perform(file::modified(path), move |result| {
+                        Message::FileModified(id, result)
+                    }))
+                });
+
+                return (Event::None, Task::batch(checks));
+            }
+            Message::FileModified(id, Ok(modified)) => {
+                let Some(tab) = self.tab_by_id(id) else {
+                    return (Event::None, Task::none());
+                };
+
+                match (tab.disk_modified, &tab.shader_path) {
+                    // First time we see this file, nothing to compare against yet
+                    (None, _) => tab.disk_modified = Some(modified),
+                    (Some(last), Some(path)) if last != modified => {
+                        return (
+                            Event::None,
+                            Task::perform(file::load(path.clone()), move |result| {
+                                Message::FileChanged(id, modified, result)
+                            }),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            Message::FileModified(_, Err(_)) => {
+                // The file may be mid-write or gone, there's nothing to reload from
             }
+            Message::FileChanged(id, modified, Ok((_, contents))) => {
+                let is_busy = self.pending.is_some();
+
+                let Some(tab) = self.tab_by_id(id) else {
+                    return (Event::None, Task::none());
+                };
+
+                // Another prompt is up, look again on the next check
+                if tab.is_dirty && is_busy {
+                    return (Event::None, Task::none());
+                }
+
+                tab.disk_modified = Some(modified);
+
+                if tab.is_saved(&contents) {
+                    return (Event::None, Task::none());
+                }
+
+                if tab.is_dirty {
+                    self.pending = Some(Pending::Changed(id, contents));
+                } else {
+                    tab.reload(&contents);
+                    return self.reloaded(id);
+                }
+            }
+            Message::FileChanged(_, _, Err(_)) => {}
             Message::Save => {
                 return if self.is_loading {
                     (Event::None, Task::none())
                 } else {
-                    let shader = self.content.text();
+                    (Event::None, save(self.tab()))
+                };
+            }
+            Message::SaveAs => {
+                let tab = self.tab();
+                let id = tab.id;
+
+                return (
+                    Event::None,
+                    Task::perform(
+                        file::save_as(
+                            tab.shader_path.clone(),
+                            Arc::new(tab.content.text()),
+                            "Save shader as...",
+                        ),
+                        move |result| Message::Saved(id, result),
+                    ),
+                );
+            }
+            Message::SaveCopy => {
+                let tab = self.tab();
+                let id = tab.id;
 
-                    (
+                return (
+                    Event::None,
+                    Task::perform(
+                        file::save_as(
+                            tab.shader_path.clone(),
+                            Arc::new(tab.content.text()),
+                            "Save a copy of the shader...",
+                        ),
+                        move |result| Message::SavedCopy(id, result),
+                    ),
+                );
+            }
+            Message::SaveTo(id, path) => {
+                if let Some(tab) = self.tabs.iter().find(|tab| tab.id == id) {
+                    return (
                         Event::None,
-                        Task::perform(file::save(self.shader_path.clone(), shader), Message::Saved),
-                    )
-                };
+                        Task::perform(
+                            file::save(Some(path), Arc::new(tab.content.text())),
+                            move |result| Message::Saved(id, result),
+                        ),
+                    );
+                }
             }
-            Message::Saved(result) => {
-                if let Ok(path) = result {
-                    self.shader_path = Some(path);
+            Message::SaveCopyTo(id, path) => {
+                if let Some(tab) = self.tabs.iter().find(|tab| tab.id == id) {
+                    return (
+                        Event::None,
+                        Task::perform(
+                            file::save(Some(path), Arc::new(tab.content.text())),
+                            move |result| Message::SavedCopy(id, result),
+                        ),
+                    );
                 }
-                // TODO: Handle error
-                return (Event::None, self.save_prefs());
+            }
+            Message::SavedCopy(id, result) => match result {
+                // The tab keeps pointing at its original file
+                Ok(_) => {}
+                Err(error) if !error.is_cancelled() => {
+                    let mut toast = Toast::error("Couldn't save a copy", &error);
+
+                    if let file::Error::IoError(path, _) = error {
+                        toast = toast.retry(Message::SaveCopyTo(id, path));
+                    }
+
+                    return (Event::Notify(toast), Task::none());
+                }
+                Err(_) => {}
+            },
+            Message::Saved(id, result) => {
+                if let Ok((path, _)) = &result {
+                    self.recent.push(path.clone());
+                }
+
+                let cmd = self.save_prefs();
+
+                match result {
+                    Ok((path, contents)) => {
+                        if let Some(tab) = self.tab_by_id(id) {
+                            tab.saved(path, &contents);
+                        }
+
+                        if self.is_saving_pending {
+                            let (event, close) = match self.pending {
+                                Some(Pending::CloseTab(pending)) if pending == id => {
+                                    self.close_tab(id)
+                                }
+                                Some(Pending::Exit) if !self.is_dirty() => {
+                                    (Event::Exit, Task::none())
+                                }
+                                _ => return (Event::None, cmd),
+                            };
+
+                            self.pending = None;
+                            self.is_saving_pending = false;
+
+                            return (event, Task::batch([cmd, close]));
+                        }
+                    }
+                    Err(error) => {
+                        // Don't close anything if a save was cancelled or failed
+                        if self.is_saving_pending {
+                            self.pending = None;
+                            self.is_saving_pending = false;
+                        }
+
+                        if !error.is_cancelled() {
+                            let title = self
+                                .tabs
+                                .iter()
+                                .find(|tab| tab.id == id)
+                                .map_or("shader".to_string(), Tab::title);
+                            let mut toast = Toast::error(format!("Couldn't save {title}"), &error);
+
+                            if let file::Error::IoError(path, _) = error {
+                                toast = toast.retry(Message::SaveTo(id, path));
+                            }
+
+                            return (Event::Notify(toast), cmd);
+                        }
+                    }
+                }
+
+                return (Event::None, cmd);
             }
             Message::Validate => {
-                self.validation_status = validation::Status::Validating;
-                let shader = Arc::new(self.content.text());
+                let target = self.target();
+
+                return (
+                    Event::None,
+                    validate(self.tab_mut(), Duration::ZERO, target),
+                );
+            }
+            Message::Validated(id, version, validated) => {
+                let active_id = self.tab().id;
+
+                let Some(tab) = self.tab_by_id(id) else {
+                    return (Event::None, Task::none());
+                };
 
+                // The buffer changed while validating, a newer result is on its way
+                if tab.version != version {
+                    return (Event::None, Task::none());
+                }
+
+                tab.pending_validation = None;
+
+                // Keep the last ones around while the shader doesn't parse, so the picker stays
+                if !validated.entry_points.is_empty() {
+                    tab.entry_points = validated.entry_points;
+                }
+
+                match validated.result {
+                    Ok(shader) => {
+                        tab.validation_status = validation::Status::Validated;
+                        tab.last_valid_shader = Some(shader);
+                        tab.params = validated.params;
+
+                        if id == active_id {
+                            return (self.pipeline(), Task::none());
+                        }
+                    }
+                    Err(error) => {
+                        println!("Invalid: {error:?}");
+                        tab.validation_status = validation::Status::Invalid(error);
+                    }
+                }
+            }
+            Message::ChangeOptions(change) => {
+                let tab = self.tab_mut();
+                tab.options.apply(change);
+
+                if let Some(path) = tab.shader_path.clone() {
+                    let options = tab.options.clone();
+                    self.files.insert(path, options);
+                }
+
+                let target = self.target();
+
+                return (
+                    Event::None,
+                    Task::batch([
+                        self.save_prefs(),
+                        validate(self.tab_mut(), Duration::ZERO, target),
+                        self.load_images(),
+                    ]),
+                );
+            }
+            Message::PickChannel(channel) => {
                 return (
                     Event::None,
-                    Task::perform(validation::validate(shader), Message::Validated),
+                    Task::perform(file::pick_image(), move |path| {
+                        Message::ChannelPicked(channel, path)
+                    }),
                 );
             }
-            Message::Validated(result) => match result {
-                Ok(shader) => {
-                    self.validation_status = validation::Status::Validated;
-                    return (Event::UpdatePipeline(shader), Task::none());
+            Message::ChannelPicked(channel, path) => {
+                let Some(path) = path else {
+                    return (Event::None, Task::none());
+                };
+
+                // Picking an image again reloads it, in case it was edited or failed to load
+                self.images.remove(&path);
+
+                return self.update(Message::ChangeOptions(options::Change::Channel(
+                    channel,
+                    Some(path),
+                )));
+            }
+            Message::ImageLoaded(path, result) => match result {
+                Ok(image) => {
+                    self.images.insert(path.clone(), Some(image));
+
+                    if self.tab().options.images().any(|used| *used == path) {
+                        return (self.pipeline(), Task::none());
+                    }
                 }
                 Err(error) => {
-                    println!("Invalid: {error:?}");
-                    self.validation_status = validation::Status::Invalid(error);
+                    return (
+                        Event::Notify(Toast::error("Couldn't load image", error)),
+                        Task::none(),
+                    );
                 }
             },
             Message::AutoValidate(checked) => {
@@ -201,79 +888,487 @@ impl Editor {
                 return (Event::None, self.save_prefs());
             }
             Message::Undo => {
-                // TODO!
+                let tab = self.tab_mut();
+                let is_valid = tab.is_valid();
+
+                if let Some(was_valid) = tab.history.undo(&mut tab.content, is_valid) {
+                    return self.restored(was_valid);
+                }
             }
             Message::Redo => {
-                // TODO!
+                let tab = self.tab_mut();
+                let is_valid = tab.is_valid();
+
+                if let Some(was_valid) = tab.history.redo(&mut tab.content, is_valid) {
+                    return self.restored(was_valid);
+                }
             }
             Message::Indent => {
-                const INDENT_SIZE: usize = 4;
-                const USE_SPACES: bool = true;
+                let indent_char = self.settings.indent();
 
-                let indent_char = if USE_SPACES {
-                    " ".repeat(INDENT_SIZE)
-                } else {
-                    "\t".to_string()
-                };
+                let tab = self.tab_mut();
+                tab.history.checkpoint(&tab.content, tab.is_valid());
 
                 for ch in indent_char.chars() {
-                    self.content.perform(Action::Edit(text_editor::Edit::Insert(ch)));
+                    tab.content.perform(Action::Edit(text_editor::Edit::Insert(ch)));
                 }
 
-                if self.auto_validate {
-                    return self.update(Message::Validate);
+                return self.changed();
+            }
+            Message::Hovered(position, viewport) => {
+                self.hover = Some(position);
+                self.viewport = viewport;
+            }
+            Message::Unhovered => {
+                self.hover = None;
+            }
+            Message::JumpTo(range) => {
+                let text = self.tab().content.text();
+
+                // The buffer may have changed since the span was found
+                if text.get(range.clone()).is_some() {
+                    self.select(&text, range);
                 }
             }
             Message::Search => {
-                // TODO!
+                let content = &self.tab().content;
+
+                // Seed the query with a single-line selection, like most editors do
+                if let Some(selection) = content.selection().filter(|s| !s.contains('\n')) {
+                    self.search.query = selection;
+                }
+
+                self.search.is_open = true;
+                self.search.find(&self.tab().text());
+
+                return (
+                    Event::None,
+                    Task::batch([
+                        text_input::focus(search::QUERY_ID.clone()),
+                        text_input::select_all(search::QUERY_ID.clone()),
+                    ]),
+                );
+            }
+            Message::CloseSearch => {
+                self.search.is_open = false;
+            }
+            Message::SearchChanged(query) => {
+                self.search.query = query;
+                self.search.find(&self.tab().text());
             }
-            Message::PreferencesSaved(_) => {
+            Message::ReplacementChanged(replacement) => {
+                self.search.replacement = replacement;
+            }
+            Message::ToggleSearchOption(toggle) => {
+                self.search.toggle(toggle);
+                self.search.find(&self.tab().text());
+            }
+            Message::FindNext => {
+                let text = self.tab().text();
+                let content = &self.tab().content;
+                let from = cursor::byte_offset(content, content.cursor_position());
+
+                if let Some(range) = self.search.next(from) {
+                    self.select(&text, range);
+                }
+            }
+            Message::FindPrevious => {
+                let text = self.tab().text();
+                let content = &self.tab().content;
+                let from = cursor::byte_offset(content, content.cursor_position());
+
+                if let Some(range) = self.search.previous(from) {
+                    self.select(&text, range);
+                }
+            }
+            Message::Replace => {
+                let text = self.tab().text();
+
+                let Some((range, replacement)) = self.search.replace(&text) else {
+                    return self.update(Message::FindNext);
+                };
+
+                self.select(&text, range);
+
+                let tab = self.tab_mut();
+                tab.history.checkpoint(&tab.content, tab.is_valid());
+                tab.content
+                    .perform(Action::Edit(text_editor::Edit::Paste(Arc::new(replacement))));
+
+                let (event, validate) = self.changed();
+                let (_, find_next) = self.update(Message::FindNext);
+
+                return (event, Task::batch([validate, find_next]));
+            }
+            Message::ReplaceAll => {
+                let text = self.tab().text();
+
+                if let Some(replaced) = self.search.replace_all(&text) {
+                    self.tab_mut().replace(replaced);
+
+                    return self.changed();
+                }
+            }
+            Message::Notify(toast) => return (Event::Notify(*toast), Task::none()),
+            Message::SavePreferences => {
+                return (Event::None, self.save_prefs());
+            }
+            Message::PreferencesSaved(Ok(())) => {
                 println!("Prefs saved");
             }
+            Message::PreferencesSaved(Err(error)) => {
+                return (
+                    Event::Notify(
+                        Toast::error("Couldn't save preferences", error)
+                            .retry(Message::SavePreferences),
+                    ),
+                    Task::none(),
+                );
+            }
         }
 
         (Event::None, Task::none())
     }
 
+    fn tab(&self) -> &Tab {
+        &self.tabs[self.active]
+    }
+
+    fn tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+
+    fn tab_by_id(&mut self, id: tab::Id) -> Option<&mut Tab> {
+        self.tabs.iter_mut().find(|tab| tab.id == id)
+    }
+
+    /// Whether any tab has changes that haven't been saved.
+    pub fn is_dirty(&self) -> bool {
+        self.tabs.iter().any(|tab| tab.is_dirty)
+    }
+
+    /// Title of the active tab, marked when it has unsaved changes.
+    pub fn title(&self) -> String {
+        let tab = self.tab();
+
+        if tab.is_dirty {
+            format!("{} •", tab.title())
+        } else {
+            tab.title()
+        }
+    }
+
+    fn close_tab(&mut self, id: tab::Id) -> (Event, Task<Message>) {
+        let Some(index) = self.tabs.iter().position(|tab| tab.id == id) else {
+            return (Event::None, Task::none());
+        };
+
+        self.tabs.remove(index);
+
+        // Always keep a tab open, without going through anything that reads the active one
+        if self.tabs.is_empty() {
+            self.tabs.push(Tab::new(
+                None,
+                include_str!("viewer/shaders/empty_frag.wgsl"),
+            ));
+            self.active = 0;
+        } else if self.active >= index && self.active > 0 {
+            self.active -= 1;
+        }
+
+        self.search.find(&self.tab().text());
+
+        (self.pipeline(), self.save_prefs())
+    }
+
+    /// Adds `tab` and switches to it, replacing the active tab if it's an untouched scratch
+    /// buffer.
+    fn open_tab(&mut self, mut tab: Tab) {
+        // Pick up the options from the last time the file was open
+        if let Some(path) = &tab.shader_path {
+            tab.options = self.files.get(path).cloned().unwrap_or_default();
+        }
+
+        let active = self.tab();
+
+        if active.shader_path.is_none() && !active.is_dirty {
+            self.tabs[self.active] = tab;
+        } else {
+            self.tabs.push(tab);
+            self.active = self.tabs.len() - 1;
+        }
+
+        self.search.find(&self.tab().text());
+    }
+
+    /// Checks open files for changes made by other programs.
+    pub fn subscription(&self) -> Subscription<Message> {
+        time::every(Duration::from_secs(1)).map(|_| Message::CheckFiles)
+    }
+
+    /// Revalidates a tab whose contents were replaced from disk.
+    fn reloaded(&mut self, id: tab::Id) -> (Event, Task<Message>) {
+        if self.tab().id == id {
+            return self.changed();
+        }
+
+        let target = self.target();
+
+        let Some(tab) = self.tab_by_id(id) else {
+            return (Event::None, Task::none());
+        };
+
+        (Event::None, validate(tab, Duration::ZERO, target))
+    }
+
+    /// Revalidates every tab, like after what they're validated against changed.
+    fn validate_all(&mut self) -> Task<Message> {
+        let target = self.target();
+
+        Task::batch(
+            self.tabs
+                .iter_mut()
+                .map(|tab| validate(tab, Duration::ZERO, target.clone())),
+        )
+    }
+
+    fn target(&self) -> profile::Target {
+        self.settings.target_profile.target(self.device.as_ref())
+    }
+
+    /// Points the viewer at the active tab's last valid shader.
+    fn pipeline(&self) -> Event {
+        let tab = self.tab();
+        let mut shader = tab.last_valid_shader.clone().unwrap_or_else(|| {
+            Shader::bundled(include_str!("viewer/shaders/empty_frag.wgsl"))
+        });
+
+        // Images load on their own time, so they're put in whenever the shader is sent
+        for (channel, picked) in shader.channels.iter_mut().zip(&tab.options.channels) {
+            if let (Some(channel), Some(picked)) = (channel, picked) {
+                channel.image = self.images.get(&picked.path).cloned().flatten();
+            }
+        }
+
+        Event::UpdatePipeline(shader, self.packed_params())
+    }
+
+    /// Sends the viewer the active tab's param values.
+    fn params(&self) -> Event {
+        Event::UpdateParams(self.packed_params())
+    }
+
+    fn packed_params(&self) -> Vec<u8> {
+        let tab = self.tab();
+
+        tab.params
+            .as_ref()
+            .map(|params| params.pack(&tab.options.params))
+            .unwrap_or_default()
+    }
+
+    /// Starts loading the images open tabs sample that haven't been yet.
+    fn load_images(&mut self) -> Task<Message> {
+        let paths: Vec<_> = self
+            .tabs
+            .iter()
+            .flat_map(|tab| tab.options.images())
+            .filter(|path| !self.images.contains_key(*path))
+            .cloned()
+            .collect();
+
+        Task::batch(paths.into_iter().map(|path| {
+            self.images.insert(path.clone(), None);
+
+            Task::perform(file::load_image(path.clone()), move |result| {
+                Message::ImageLoaded(path.clone(), result)
+            })
+        }))
+    }
+
+    /// Keeps search results and validation in sync after the buffer was edited.
+    fn changed(&mut self) -> (Event, Task<Message>) {
+        self.tab_mut().touch();
+
+        if self.search.is_open {
+            self.search.find(&self.tab().text());
+        }
+
+        if !self.auto_validate {
+            return (Event::None, Task::none());
+        }
+
+        // Wait for typing to settle instead of validating every keystroke
+        let delay = self.settings.validation_delay();
+        let target = self.target();
+
+        (Event::None, validate(self.tab_mut(), delay, target))
+    }
+
+    /// Revalidates after undo/redo swapped the buffer, so landing back on a shader that used to
+    /// be valid updates the viewer again.
+    fn restored(&mut self, was_valid: bool) -> (Event, Task<Message>) {
+        self.tab_mut().touch();
+
+        if self.search.is_open {
+            self.search.find(&self.tab().text());
+        }
+
+        if self.auto_validate || was_valid {
+            self.update(Message::Validate)
+        } else {
+            self.tab_mut().validation_status = validation::Status::NeedsValidation;
+            (Event::None, Task::none())
+        }
+    }
+
+    /// Selects the byte `range` of `text`, which must be the current buffer.
+    fn select(&mut self, text: &str, range: Range<usize>) {
+        let anchor = text[..range.start].chars().count();
+        let cursor = anchor + text[range].chars().count();
+
+        cursor::select(&mut self.tab_mut().content, anchor, cursor);
+    }
+
+    fn line_height(&self) -> f32 {
+        self.settings.font_size * self.settings.line_height
+    }
+
+    /// How much of the editor's height the text can take up, once it's been seen.
+    fn text_height(&self) -> f32 {
+        self.viewport.height - self.settings.padding * 2.0
+    }
+
+    /// The diagnostic under the mouse and where the mouse is. iced doesn't tell us what's under
+    /// the mouse, so this works it out from the editor's metrics.
+    fn hovered(&self, lines: &Lines, diagnostics: Vec<Diagnostic>) -> Option<(Point, Diagnostic)> {
+        let position = self.hover?;
+        let char_width = self.char_width();
+
+        let y = position.y - self.settings.padding;
+        let column = (position.x - self.settings.padding) / char_width;
+
+        if y < 0.0 || column < 0.0 {
+            return None;
+        }
+
+        // Lines never wrap, so each one takes up a single row
+        let line = (self.tab().scroll + y) / self.line_height();
+        let offset = lines.offset(line as usize, column as usize, TAB_WIDTH)?;
+
+        diagnostics::at(diagnostics, offset).map(|diagnostic| (position, diagnostic))
+    }
+
+    /// How far the editor's font advances per glyph, measured the way the editor lays text out.
+    fn char_width(&self) -> f32 {
+        let glyph = graphics::text::Paragraph::with_text(advanced::Text {
+            content: "0",
+            bounds: Size::INFINITY,
+            size: self.settings.font_size.into(),
+            line_height: LineHeight::Relative(self.settings.line_height),
+            font: self.settings.font.font(),
+            horizontal_alignment: Horizontal::Left,
+            vertical_alignment: Vertical::Top,
+            shaping: text::Shaping::Advanced,
+            wrapping: text::Wrapping::None,
+        });
+
+        glyph.min_width()
+    }
+
     fn save_prefs(&self) -> Task<Message> {
+        let open_tabs = self
+            .tabs
+            .iter()
+            .filter_map(|tab| tab.shader_path.clone())
+            .collect();
+
+        // Unsaved tabs aren't restored, so count only the ones before the active tab that are
+        let active_tab = self.tabs[..self.active]
+            .iter()
+            .filter(|tab| tab.shader_path.is_some())
+            .count();
+
         let prefs = Preferences {
-            last_shader_path: self.shader_path.clone(),
+            open_tabs,
+            active_tab,
+            recent_files: self.recent.files().to_vec(),
             auto_validate: self.auto_validate,
+            editor: self.settings.clone(),
+            files: self.files(),
+            ..Preferences::default()
         };
 
         Task::perform(preferences::save(prefs), Message::PreferencesSaved)
     }
 
+    /// Options to remember, including ones changed before an untitled tab was saved. Files left
+    /// at the defaults aren't worth writing down.
+    fn files(&self) -> BTreeMap<PathBuf, Options> {
+        let mut files = self.files.clone();
+
+        for tab in &self.tabs {
+            if let Some(path) = &tab.shader_path {
+                files.insert(path.clone(), tab.options.clone());
+            }
+        }
+
+        files.retain(|_, options| *options != Options::default());
+        files
+    }
+
     pub fn view(&'_ self) -> Element<'_, Message> {
-        let errors =
-            if let validation::Status::Invalid(validation::Error::Parse { message: _, errors }) =
-                &self.validation_status
-            {
-                errors
-                    .iter()
-                    .map(|(range, _msg)| range)
-                    .cloned()
-                    .collect::<Vec<_>>()
-            } else {
-                vec![]
-            };
+        let tab = self.tab();
+        let source = tab.content.text();
+        let lines = Lines::new(&source);
 
-        let text_editor = text_editor(&self.content)
-            .font(JETBRAINS_MONO)
-            .padding(10)
-            .height(Length::Fill)
-            .key_binding(|key| self.keypress(key))
-            .highlight_with::<Highlighter>(
-                highlighter::Settings {
-                    theme: self.theme,
-                    errors,
-                },
-                |highlight, _theme| highlight.to_format(),
-            )
-            .on_action(Message::Action);
+        let diagnostics = match &tab.validation_status {
+            validation::Status::Invalid(error) => error.diagnostics(),
+            _ => vec![],
+        };
+
+        let marks = diagnostics::marks(&diagnostics, &lines);
+        let matches = self.search.highlights(&source);
+        let hovered = self.hovered(&lines, diagnostics.clone());
+
+        let text_editor = responsive(move |size| {
+            let text_editor = text_editor(&tab.content)
+                .font(self.settings.font.font())
+                .size(self.settings.font_size)
+                .line_height(LineHeight::Relative(self.settings.line_height))
+                .padding(self.settings.padding)
+                .height(Length::Fill)
+                .wrapping(text::Wrapping::None)
+                .key_binding(|key| self.keypress(key))
+                .highlight_with::<Highlighter>(
+                    highlighter::Settings {
+                        theme: self.settings.syntax_theme,
+                        marks: marks.clone(),
+                        matches: matches.clone(),
+                    },
+                    |highlight, theme| highlight.to_format(theme),
+                )
+                .on_action(Message::Action);
+
+            let text_editor = mouse_area(text_editor)
+                .on_move(move |position| Message::Hovered(position, size))
+                .on_exit(Message::Unhovered);
+
+            // Show the message just below the hovered line
+            let tooltip = hovered.clone().map(|(position, diagnostic)| {
+                container(diagnostics::tooltip(diagnostic))
+                    .padding(Padding {
+                        top: position.y + self.line_height(),
+                        left: position.x,
+                        ..Padding::ZERO
+                    })
+            });
+
+            stack![text_editor].push_maybe(tooltip).into()
+        });
 
         let path = container(text(
-            self.shader_path
+            tab.shader_path
                 .as_ref()
                 .map_or("".to_string(), |p| p.to_string_lossy().to_string()),
         ))
@@ -282,40 +1377,182 @@ impl Editor {
 
         let char_count = container(
             // TODO: Expose a len() function from iced editor to avoid extra allocation
-            text(self.content.text().len().to_string() + " chars"),
+            text(source.len().to_string() + " chars"),
         )
         .align_x(Horizontal::Right);
 
         let info = row![path, char_count].width(Length::Fill).padding([5, 10]);
 
-        let content =
-            if let validation::Status::Invalid(validation::Error::Parse { message, errors }) =
-                &self.validation_status
-            {
-                column![
-                    text_editor,
-                    tmp_error_view(message, &errors, &self.content.text()),
-                    info,
-                ]
-                .height(Length::Fill)
+        let search = self.search.is_open.then(|| self.search.view());
+        let content = column![self.tab_bar()]
+            .push_maybe(search)
+            .push(text_editor);
+
+        let content = if diagnostics.is_empty() {
+            content
+        } else {
+            content.push(diagnostics::view(diagnostics, &source))
+        }
+        .push(info)
+        .height(Length::Fill);
+
+        let content = container(content)
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        if self.recent.is_open {
+            stack![content, dropdown(self.recent.view(), Message::ToggleRecent)].into()
+        } else if self.is_settings_open {
+            stack![content, dropdown(self.settings.view(), Message::ToggleSettings)].into()
+        } else if self.is_options_open {
+            let tab = self.tab();
+            let selected = tab
+                .last_valid_shader
+                .as_ref()
+                .and_then(|shader| shader.vertex.as_ref())
+                .map(|vertex| vertex.entry_point.clone());
+
+            let options = tab.options.view(&tab.entry_points, selected);
+
+            stack![content, dropdown(options, Message::ToggleOptions)].into()
+        } else if self.is_params_open {
+            let tab = self.tab();
+            let params = match &tab.params {
+                Some(params) => params.view(&tab.options.params),
+                None => params::placeholder(),
+            };
+
+            stack![content, dropdown(params, Message::ToggleParams)].into()
+        } else {
+            content.into()
+        }
+    }
+
+    /// The unsaved changes dialog, if an action is waiting on it.
+    pub fn prompt(&'_ self) -> Option<Element<'_, Message>> {
+        use prompt::Choice;
+
+        let pending = self.pending.as_ref().filter(|_| !self.is_saving_pending)?;
+        let title = |id| {
+            self.tabs
+                .iter()
+                .find(|tab| tab.id == id)
+                .map(Tab::title)
+        };
+
+        let save_or_discard = [
+            ("Cancel", Choice::Cancel),
+            ("Discard", Choice::Discard),
+            ("Save", Choice::Save),
+        ];
+
+        Some(match pending {
+            Pending::CloseTab(id) => prompt::view(
+                "Unsaved changes",
+                format!("Save changes to {} before closing it?", title(*id)?),
+                &save_or_discard,
+            ),
+            Pending::Exit => {
+                let count = self.tabs.iter().filter(|tab| tab.is_dirty).count();
+
+                prompt::view(
+                    "Unsaved changes",
+                    format!("{count} shader(s) have unsaved changes. Save them before quitting?"),
+                    &save_or_discard,
+                )
+            }
+            Pending::Changed(id, _) => prompt::view(
+                "File changed on disk",
+                format!(
+                    "{} was changed by another program while you have unsaved edits.",
+                    title(*id)?
+                ),
+                &[
+                    ("Keep mine", Choice::Cancel),
+                    ("Reload", Choice::Discard),
+                    ("Merge", Choice::Merge),
+                ],
+            ),
+        })
+    }
+
+    fn tab_bar(&'_ self) -> Element<'_, Message> {
+        let tabs = self.tabs.iter().enumerate().map(|(index, tab)| {
+            let title = if tab.is_dirty {
+                format!("{} •", tab.title())
             } else {
-                column![text_editor, info].height(Length::Fill)
+                tab.title()
             };
 
-        container(content)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .into()
+            let label = row![
+                text(title).size(14),
+                button(icon(Icon::X))
+                    .padding(0)
+                    .class(ButtonClass::Tab)
+                    .on_press(Message::CloseTab(tab.id)),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center);
+
+            button(label)
+                .padding([5, 10])
+                .class(if index == self.active {
+                    ButtonClass::ActiveTab
+                } else {
+                    ButtonClass::Tab
+                })
+                .on_press(Message::SelectTab(tab.id))
+                .into()
+        });
+
+        container(
+            scrollable(row(tabs).spacing(2))
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::new().width(2).scroller_width(2),
+                ))
+                .width(Length::Fill),
+        )
+        .width(Length::Fill)
+        .class(ContainerClass::Controls)
+        .into()
+    }
+
+    /// Only shown when there's a choice to make.
+    fn entry_point_picker(&'_ self) -> Option<Element<'_, Message>> {
+        let tab = self.tab();
+
+        if tab.entry_points.fragment.len() < 2 {
+            return None;
+        }
+
+        let selected = tab.options.entry_point.clone().or_else(|| {
+            tab.last_valid_shader
+                .as_ref()
+                .map(|shader| shader.entry_point.clone())
+        });
+
+        let picker = pick_list(tab.entry_points.fragment.as_slice(), selected, |entry_point| {
+            Message::ChangeOptions(options::Change::EntryPoint(entry_point))
+        });
+
+        Some(
+            tooltip(picker, "Fragment entry point", tooltip::Position::Bottom)
+                .padding(10)
+                .class(ContainerClass::Tooltip)
+                .into(),
+        )
     }
 
     pub fn title_bar(&'_ self) -> Element<'_, Message> {
         let validation_controls = container(
             row![
-                container(self.validation_status.icon())
+                container(self.tab().validation_status.icon())
                     .width(24)
                     .center_y(24),
                 checkbox("Auto", self.auto_validate).on_toggle(Message::AutoValidate),
+                text(self.title()),
             ]
+            .push_maybe(self.entry_point_picker())
             .spacing(10)
             .align_y(Alignment::Center),
         )
@@ -326,7 +1563,33 @@ impl Editor {
             row![
                 control_button(icon(Icon::FilePlus), "Create a new shader", Message::New),
                 control_button(icon(Icon::FolderOpen), "Open a shader file", Message::Open),
+                toggle_button(
+                    icon(Icon::History),
+                    "Open a recent shader",
+                    self.recent.is_open,
+                    Message::ToggleRecent,
+                ),
+                toggle_button(
+                    icon(Icon::Layers),
+                    "Passes and geometry",
+                    self.is_options_open,
+                    Message::ToggleOptions,
+                ),
+                toggle_button(
+                    icon(Icon::SlidersHorizontal),
+                    "Shader parameters",
+                    self.is_params_open,
+                    Message::ToggleParams,
+                ),
+                toggle_button(
+                    icon(Icon::Settings),
+                    "Editor settings",
+                    self.is_settings_open,
+                    Message::ToggleSettings,
+                ),
                 control_button(icon(Icon::Save), "Save current shader", Message::Save),
+                control_button(icon(Icon::FilePen), "Save shader as...", Message::SaveAs),
+                control_button(icon(Icon::Copy), "Save a copy of the shader", Message::SaveCopy),
             ]
             .spacing(10)
             .align_y(Alignment::Center),
@@ -346,12 +1609,74 @@ impl Editor {
     }
 }
 
+fn save(tab: &Tab) -> Task<Message> {
+    let id = tab.id;
+
+    Task::perform(
+        file::save(tab.shader_path.clone(), Arc::new(tab.content.text())),
+        move |result| Message::Saved(id, result),
+    )
+}
+
+fn open_failed(error: file::Error) -> Toast {
+    let toast = Toast::error("Couldn't open shader", &error);
+
+    match error {
+        file::Error::IoError(path, _) => toast.retry(Message::OpenFile(path)),
+        _ => toast,
+    }
+}
+
+/// Validates `tab` once `delay` has passed, cancelling any validation it still had pending.
+/// Results are tagged with the tab's version so ones for an older buffer can be dropped.
+fn validate(tab: &mut Tab, delay: Duration, target: profile::Target) -> Task<Message> {
+    let (id, version) = (tab.id, tab.version);
+    let shader = Arc::new(tab.content.text());
+    let options = tab.options.clone();
+
+    let (task, handle) = Task::perform(
+        async move {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+
+            validation::validate(shader, target, options).await
+        },
+        move |validated| Message::Validated(id, version, validated),
+    )
+    .abortable();
+
+    tab.validation_status = validation::Status::Validating;
+    // Dropping the previous handle aborts it
+    tab.pending_validation = Some(handle.abort_on_drop());
+
+    task
+}
+
 fn icon<'a>(icon: Icon) -> Element<'a, Message> {
     text(icon.unicode().to_string())
         .font(Font::with_name("lucide"))
         .into()
 }
 
+/// A menu hanging from the right of the title bar, closed by clicking anywhere else.
+fn dropdown<'a>(
+    menu: impl Into<Element<'a, Message>>,
+    on_close: Message,
+) -> Element<'a, Message> {
+    let menu = container(menu).padding(10).class(ContainerClass::Dialog);
+
+    mouse_area(
+        container(opaque(menu))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_right(Length::Fill)
+            .padding([5, 15]),
+    )
+    .on_press(on_close)
+    .into()
+}
+
 fn control_button<'a>(
     content: impl Into<Element<'a, Message>>,
     label: &'a str,
@@ -365,38 +1690,20 @@ fn control_button<'a>(
         .into()
 }
 
-fn tmp_error_view<'a>(
-    msg: &str,
-    errors: &[(Range<usize>, String)],
-    shader: &str,
+fn toggle_button<'a>(
+    content: impl Into<Element<'a, Message>>,
+    label: &'a str,
+    is_toggled: bool,
+    on_press: Message,
 ) -> Element<'a, Message> {
-    let errors = errors
-        .iter()
-        .map(|(range, err_msg)| {
-            let slice = shader.get(range.start..=range.end);
-            if let Some(slice) = slice {
-                // TODO: Can't render tabs..?
-                text(format!("{msg}:\n    {err_msg}:\n        {slice}"))
-            } else {
-                text(format!("{msg}:\n    {err_msg}"))
-            }
-            .class(TextClass::Error)
-            .size(14)
-            .into()
-        })
-        .collect::<Vec<Element<'a, Message>>>();
+    let button = button(container(content).center_x(30)).class(if is_toggled {
+        ButtonClass::Toggled
+    } else {
+        ButtonClass::Control
+    });
 
-    container(
-        scrollable(
-            column(errors)
-                .width(Length::Fill)
-                .padding([10, 20])
-                .spacing(10),
-        )
-        .width(Length::Fill)
-        .height(100),
-    )
-    .width(Length::Fill)
-    .class(ContainerClass::Error)
-    .into()
+    tooltip(button.on_press(on_press), label, tooltip::Position::Bottom)
+        .padding(10)
+        .class(ContainerClass::Tooltip)
+        .into()
 }
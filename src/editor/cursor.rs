@@ -0,0 +1,55 @@
+use iced::widget::text_editor::{Action, Content, Motion};
+
+// iced only lets us move the cursor through motions, so positions are restored by walking
+// there one character at a time. Offsets are in chars, counting line breaks as one.
+
+/// Converts a `(line, byte index)` cursor position into a char offset into the whole buffer.
+pub fn offset(content: &Content, (line, index): (usize, usize)) -> usize {
+    content
+        .lines()
+        .take(line + 1)
+        .enumerate()
+        .map(|(i, text)| {
+            if i == line {
+                text.get(..index).unwrap_or(&text).chars().count()
+            } else {
+                // Account for the line break
+                text.chars().count() + 1
+            }
+        })
+        .sum()
+}
+
+/// Moves the cursor to `offset`, clearing any selection.
+pub fn move_to(content: &mut Content, offset: usize) {
+    content.perform(Action::Move(Motion::DocumentStart));
+    repeat(content, Action::Move(Motion::Right), offset);
+}
+
+/// Selects from `anchor` to `cursor`, leaving the cursor at `cursor`.
+pub fn select(content: &mut Content, anchor: usize, cursor: usize) {
+    move_to(content, anchor);
+
+    if cursor < anchor {
+        repeat(content, Action::Select(Motion::Left), anchor - cursor);
+    } else {
+        repeat(content, Action::Select(Motion::Right), cursor - anchor);
+    }
+}
+
+fn repeat(content: &mut Content, action: Action, count: usize) {
+    for _ in 0..count {
+        content.perform(action.clone());
+    }
+}
+
+/// Converts a `(line, byte index)` cursor position into a byte offset into
+/// [`Content::text`].
+pub fn byte_offset(content: &Content, (line, index): (usize, usize)) -> usize {
+    content
+        .lines()
+        .take(line)
+        .map(|text| text.len() + 1)
+        .sum::<usize>()
+        + index
+}
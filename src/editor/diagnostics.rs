@@ -0,0 +1,181 @@
+use crate::JETBRAINS_MONO;
+use crate::editor::highlighter::{Lines, Mark};
+use crate::editor::source_map::Location;
+use crate::editor::{Element, Message};
+use crate::theme::{ButtonClass, ContainerClass, TextClass};
+use iced::Length;
+use iced::widget::{button, column, container, row, scrollable, text};
+
+/// Tabs are drawn as nothing, so snippets spell them out.
+const TAB: &str = "    ";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    /// Extra context for an error, like where a clashing name was first declared.
+    Note,
+}
+
+/// A single problem found in a shader.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// What naga says about this particular span.
+    pub label: String,
+    /// Errors that aren't about a specific span, like a missing entry point, have none.
+    pub location: Option<Location>,
+}
+
+/// The spans to mark in the editor, split per line. Errors come last so they're drawn over notes.
+pub fn marks(diagnostics: &[Diagnostic], lines: &Lines) -> Vec<Mark> {
+    let mut marks: Vec<Mark> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            // Errors in the prelude have nothing in the buffer to mark
+            let range = diagnostic.location.as_ref()?.range()?;
+
+            // Keep zero width spans, like a missing semicolon, visible
+            let range = range.start..range.end.max(range.start + 1);
+
+            Some(lines.split(range).map(|(line, range)| Mark {
+                line,
+                range,
+                severity: diagnostic.severity,
+            }))
+        })
+        .flatten()
+        .collect();
+
+    marks.sort_by_key(|mark| mark.severity == Severity::Error);
+    marks
+}
+
+/// The diagnostic marking the byte at `offset`, errors first.
+pub fn at(diagnostics: Vec<Diagnostic>, offset: usize) -> Option<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            diagnostic
+                .location
+                .as_ref()
+                .and_then(Location::range)
+                .is_some_and(|range| range.start <= offset && offset < range.end.max(range.start + 1))
+        })
+        .min_by_key(|diagnostic| diagnostic.severity != Severity::Error)
+}
+
+pub fn tooltip<'a>(diagnostic: Diagnostic) -> Element<'a, Message> {
+    let mut content = column![text(diagnostic.message)];
+
+    if !diagnostic.label.is_empty() {
+        content = content.push(text(diagnostic.label).class(TextClass::Disabled));
+    }
+
+    container(content.spacing(2))
+        .max_width(480)
+        .padding(10)
+        .class(ContainerClass::Tooltip)
+        .into()
+}
+
+pub fn view<'a>(diagnostics: Vec<Diagnostic>, shader: &str) -> Element<'a, Message> {
+    let count = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+
+    let entries = column(
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| entry(diagnostic, shader)),
+    )
+    .width(Length::Fill)
+    .spacing(2);
+
+    container(
+        column![
+            text(format!("{count} error(s)")).class(TextClass::Error),
+            scrollable(entries).height(150),
+        ]
+        .spacing(5),
+    )
+    .width(Length::Fill)
+    .padding([5, 10])
+    .class(ContainerClass::Error)
+    .into()
+}
+
+fn entry<'a>(diagnostic: Diagnostic, shader: &str) -> Element<'a, Message> {
+    let (severity, class) = match diagnostic.severity {
+        Severity::Error => ("error", TextClass::Error),
+        Severity::Note => ("note", TextClass::Warning),
+    };
+
+    let location = match &diagnostic.location {
+        Some(location) => format!("{location}: "),
+        None => String::new(),
+    };
+
+    let mut details = column![
+        row![
+            text(severity).class(class.clone()),
+            text(format!("{location}{}", diagnostic.message)),
+        ]
+        .spacing(10),
+    ]
+    .spacing(2);
+
+    if !diagnostic.label.is_empty() && diagnostic.label != diagnostic.message {
+        details = details.push(text(diagnostic.label.clone()).class(TextClass::Disabled));
+    }
+
+    if let Some(snippet) = diagnostic
+        .location
+        .as_ref()
+        .and_then(|location| snippet(shader, location))
+    {
+        details = details.push(text(snippet).font(JETBRAINS_MONO).class(class));
+    }
+
+    let entry = button(details)
+        .width(Length::Fill)
+        .class(ButtonClass::MenuItem);
+
+    // Only spans in the user's buffer have somewhere to jump to
+    match diagnostic.location.and_then(|location| location.range()) {
+        Some(range) => entry.on_press(Message::JumpTo(range)).into(),
+        None => entry.into(),
+    }
+}
+
+/// The line the error starts on with a caret under the erroring span.
+fn snippet(shader: &str, location: &Location) -> Option<String> {
+    let Location::Shader {
+        range,
+        line,
+        column,
+    } = location
+    else {
+        return None;
+    };
+
+    let source = shader.lines().nth(*line)?;
+
+    let before: String = source.chars().take(*column).collect();
+    let span: String = shader
+        .get(range.clone())?
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    let width = |text: &str| text.replace('\t', TAB).chars().count();
+
+    Some(format!(
+        "{}\n{}{}",
+        source.replace('\t', TAB),
+        " ".repeat(width(&before)),
+        "^".repeat(width(&span).max(1)),
+    ))
+}
@@ -1,40 +1,102 @@
 use crate::FragmentShader;
+use crate::viewer::Image;
+use std::fmt::{Display, Formatter};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::io;
 
 const FILE_EXT_FILTERS: [&'static str; 1] = ["wgsl"];
+const IMAGE_EXT_FILTERS: [&str; 4] = ["png", "jpg", "jpeg", "hdr"];
 
 pub async fn load(path: PathBuf) -> Result<(PathBuf, Arc<FragmentShader>), Error> {
     let contents = tokio::fs::read_to_string(&path)
         .await
         .map(Arc::new)
-        .map_err(|error| Error::IoError(error.kind()))?;
+        .map_err(|error| Error::IoError(path.clone(), error.kind()))?;
 
     Ok((path, contents))
 }
 
-pub async fn save(path: Option<PathBuf>, contents: String) -> Result<PathBuf, Error> {
+pub async fn modified(path: PathBuf) -> Result<SystemTime, Error> {
+    tokio::fs::metadata(&path)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .map_err(|error| Error::IoError(path.clone(), error.kind()))
+}
+
+/// The `paths` that no longer point to a file.
+pub async fn missing(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut missing = Vec::new();
+
+    for path in paths {
+        if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            missing.push(path);
+        }
+    }
+
+    missing
+}
+
+pub async fn save(
+    path: Option<PathBuf>,
+    contents: Arc<FragmentShader>,
+) -> Result<(PathBuf, Arc<FragmentShader>), Error> {
     println!("Saving shader at path: {path:?}");
     let path = if let Some(path) = path {
         path
     } else {
-        // TODO: This lags UI
-        rfd::AsyncFileDialog::new()
-            .add_filter("supported shader extensions", &FILE_EXT_FILTERS)
-            .save_file()
-            .await
-            .as_ref()
-            .map(rfd::FileHandle::path)
-            .map(Path::to_owned)
-            .ok_or(Error::SaveDialogueClosed)?
+        pick_save_path(None, "Save a WGSL file...").await?
     };
 
-    tokio::fs::write(&path, contents)
+    write(path, contents).await
+}
+
+/// Always asks where to save, starting from `current` if there is one.
+pub async fn save_as(
+    current: Option<PathBuf>,
+    contents: Arc<FragmentShader>,
+    title: &str,
+) -> Result<(PathBuf, Arc<FragmentShader>), Error> {
+    let path = pick_save_path(current.as_deref(), title).await?;
+
+    write(path, contents).await
+}
+
+async fn pick_save_path(current: Option<&Path>, title: &str) -> Result<PathBuf, Error> {
+    let mut dialog = rfd::AsyncFileDialog::new()
+        .add_filter("supported shader extensions", &FILE_EXT_FILTERS)
+        .set_title(title);
+
+    if let Some(current) = current {
+        if let Some(directory) = current.parent() {
+            dialog = dialog.set_directory(directory);
+        }
+
+        if let Some(name) = current.file_name() {
+            dialog = dialog.set_file_name(name.to_string_lossy());
+        }
+    }
+
+    // TODO: This lags UI
+    dialog
+        .save_file()
         .await
-        .map_err(|error| Error::IoError(error.kind()))?;
+        .as_ref()
+        .map(rfd::FileHandle::path)
+        .map(Path::to_owned)
+        .ok_or(Error::SaveDialogueClosed)
+}
 
-    Ok(path)
+async fn write(
+    path: PathBuf,
+    contents: Arc<FragmentShader>,
+) -> Result<(PathBuf, Arc<FragmentShader>), Error> {
+    tokio::fs::write(&path, contents.as_bytes())
+        .await
+        .map_err(|error| Error::IoError(path.clone(), error.kind()))?;
+
+    Ok((path, contents))
 }
 
 pub async fn open() -> Result<(PathBuf, Arc<String>), Error> {
@@ -48,9 +110,50 @@ pub async fn open() -> Result<(PathBuf, Arc<String>), Error> {
     load(shader.path().to_owned()).await
 }
 
+/// Asks for an image for a channel to sample, `None` if the dialogue was closed.
+pub async fn pick_image() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .add_filter("supported image extensions", &IMAGE_EXT_FILTERS)
+        .set_title("Pick an image...")
+        .pick_file()
+        .await
+        .map(|image| image.path().to_owned())
+}
+
+/// Decodes the image at `path`, which is slow enough for big files to keep off the UI thread.
+pub async fn load_image(path: PathBuf) -> Result<Arc<Image>, Error> {
+    let error_path = path.clone();
+
+    tokio::task::spawn_blocking(move || Image::open(&path))
+        .await
+        .map_err(|error| Error::Image(error_path.clone(), error.to_string()))?
+        .map(Arc::new)
+        .map_err(|error| Error::Image(error_path, error.to_string()))
+}
+
 #[derive(Debug, Clone)]
 pub enum Error {
-    IoError(io::ErrorKind),
+    IoError(PathBuf, io::ErrorKind),
+    /// The image couldn't be read or decoded.
+    Image(PathBuf, String),
     SaveDialogueClosed,
     OpenDialogueClosed,
 }
+
+impl Error {
+    /// Whether the user backed out of a dialogue, which isn't worth telling them about.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Error::SaveDialogueClosed | Error::OpenDialogueClosed)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::IoError(path, kind) => write!(f, "{}: {kind}", path.display()),
+            Error::Image(path, error) => write!(f, "{}: {error}", path.display()),
+            Error::SaveDialogueClosed => write!(f, "The save dialogue was closed"),
+            Error::OpenDialogueClosed => write!(f, "The open dialogue was closed"),
+        }
+    }
+}
@@ -1,6 +1,10 @@
+use crate::editor::diagnostics::Severity;
+use crate::theme::Theme;
 use iced::advanced::text::highlighter::Format;
 use iced::{Color, Font};
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use std::ops::Range;
 use syntect::highlighting::StyleModifier;
 use syntect::parsing;
@@ -15,36 +19,166 @@ static THEMES: Lazy<syntect::highlighting::ThemeSet> =
 
 const LINES_PER_SNAPSHOT: usize = 50;
 
+/// The syntect themes bundled with `ThemeSet::load_defaults`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyntaxTheme {
+    #[default]
+    Base16Mocha,
+    Base16Ocean,
+    Base16Eighties,
+    SolarizedDark,
+    InspiredGitHub,
+}
+
+impl SyntaxTheme {
+    pub const ALL: [SyntaxTheme; 5] = [
+        SyntaxTheme::Base16Mocha,
+        SyntaxTheme::Base16Ocean,
+        SyntaxTheme::Base16Eighties,
+        SyntaxTheme::SolarizedDark,
+        SyntaxTheme::InspiredGitHub,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            SyntaxTheme::Base16Mocha => "base16-mocha.dark",
+            SyntaxTheme::Base16Ocean => "base16-ocean.dark",
+            SyntaxTheme::Base16Eighties => "base16-eighties.dark",
+            SyntaxTheme::SolarizedDark => "Solarized (dark)",
+            SyntaxTheme::InspiredGitHub => "InspiredGitHub",
+        }
+    }
+}
+
+impl Display for SyntaxTheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SyntaxTheme::Base16Mocha => "Mocha",
+            SyntaxTheme::Base16Ocean => "Ocean",
+            SyntaxTheme::Base16Eighties => "Eighties",
+            SyntaxTheme::SolarizedDark => "Solarized Dark",
+            SyntaxTheme::InspiredGitHub => "Inspired GitHub",
+        })
+    }
+}
+
+/// Where each line of a buffer starts, to split byte ranges over the whole buffer into ranges
+/// within lines.
+pub struct Lines<'a> {
+    text: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Self { text, starts }
+    }
+
+    /// The parts of `range` on each line it spans, as `(line, range within the line)`.
+    pub fn split(&self, range: Range<usize>) -> impl Iterator<Item = (usize, Range<usize>)> + '_ {
+        let first = self.starts.partition_point(|&start| start <= range.start) - 1;
+
+        self.starts[first..]
+            .iter()
+            .enumerate()
+            .take_while(move |(_, start)| **start < range.end)
+            .map(move |(i, &start)| {
+                let line = first + i;
+
+                (
+                    line,
+                    range.start.saturating_sub(start)..range.end.min(self.end(line)) - start,
+                )
+            })
+    }
+
+    /// The byte offset of the char drawn over `column` on `line`, if there is one. Tabs reach
+    /// to the next multiple of `tab_width` columns.
+    pub fn offset(&self, line: usize, column: usize, tab_width: usize) -> Option<usize> {
+        let start = *self.starts.get(line)?;
+        let mut end = 0;
+
+        self.text[start..self.end(line)]
+            .char_indices()
+            .find(|&(_, char)| {
+                end = match char {
+                    '\t' => (end / tab_width + 1) * tab_width,
+                    _ => end + 1,
+                };
+
+                column < end
+            })
+            .map(|(index, _)| start + index)
+    }
+
+    fn end(&self, line: usize) -> usize {
+        self.starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next_start| next_start - 1)
+    }
+}
+
 pub struct Highlighter {
     syntax: &'static parsing::SyntaxReference,
     highlighter: syntect::highlighting::Highlighter<'static>,
     caches: Vec<(parsing::ParseState, parsing::ScopeStack)>,
     current_line: usize,
-    errors: Vec<Range<usize>>,
+    theme: SyntaxTheme,
+    marks: Vec<Mark>,
+    matches: Vec<Match>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Settings {
-    pub theme: iced::highlighter::Theme,
-    pub errors: Vec<Range<usize>>,
+    pub theme: SyntaxTheme,
+    pub marks: Vec<Mark>,
+    pub matches: Vec<Match>,
 }
 
-pub struct Highlight(StyleModifier);
+/// A diagnostic's span within a single line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mark {
+    pub line: usize,
+    pub range: Range<usize>,
+    pub severity: Severity,
+}
+
+/// A search match within a single line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    pub line: usize,
+    pub range: Range<usize>,
+    pub is_current: bool,
+}
+
+pub enum Highlight {
+    Syntax(StyleModifier),
+    Mark(Severity),
+    Match { is_current: bool },
+}
 
 impl Highlight {
-    pub fn color(&self) -> Option<Color> {
-        self.0
-            .foreground
-            .map(|color| Color::from_rgba8(color.r, color.g, color.b, color.a as f32 / 255.0))
+    pub fn color(&self, theme: &Theme) -> Option<Color> {
+        match self {
+            Highlight::Syntax(modifier) => modifier.foreground.map(|color| {
+                Color::from_rgba8(color.r, color.g, color.b, color.a as f32 / 255.0)
+            }),
+            Highlight::Mark(severity) => Some(theme.diagnostic(*severity == Severity::Error)),
+            Highlight::Match { is_current } => Some(theme.search_match(*is_current)),
+        }
     }
 
     pub fn font(&self) -> Option<Font> {
         None
     }
 
-    pub fn to_format(&self) -> Format<Font> {
+    pub fn to_format(&self, theme: &Theme) -> Format<Font> {
         Format {
-            color: self.color(),
+            color: self.color(theme),
             font: self.font(),
         }
     }
@@ -63,7 +197,7 @@ impl iced::advanced::text::Highlighter for Highlighter {
             .unwrap_or_else(|| WGSL_SYNTAX.find_syntax_plain_text());
 
         let highlighter =
-            syntect::highlighting::Highlighter::new(&THEMES.themes["base16-mocha.dark"]);
+            syntect::highlighting::Highlighter::new(&THEMES.themes[settings.theme.key()]);
 
         let parser = parsing::ParseState::new(syntax);
         let stack = parsing::ScopeStack::new();
@@ -73,12 +207,20 @@ impl iced::advanced::text::Highlighter for Highlighter {
             highlighter,
             caches: vec![(parser, stack)],
             current_line: 0,
-            errors: settings.errors.clone(),
+            theme: settings.theme,
+            marks: settings.marks.clone(),
+            matches: settings.matches.clone(),
         }
     }
 
     fn update(&mut self, new_settings: &Self::Settings) {
-        self.errors = new_settings.errors.clone();
+        if self.theme != new_settings.theme {
+            *self = Self::new(new_settings);
+            return;
+        }
+
+        self.marks = new_settings.marks.clone();
+        self.matches = new_settings.matches.clone();
         self.current_line = 0;
     }
 
@@ -112,6 +254,7 @@ impl iced::advanced::text::Highlighter for Highlighter {
             self.caches.push((parser.clone(), stack.clone()));
         }
 
+        let line_index = self.current_line;
         self.current_line += 1;
 
         let (parser, stack) = self.caches.last_mut().expect("Caches must not be empty");
@@ -121,6 +264,27 @@ impl iced::advanced::text::Highlighter for Highlighter {
 
         let highlighter = &self.highlighter;
 
+        // Later spans override earlier ones, so diagnostics are layered on top of the syntax and
+        // search matches on top of both
+        let marks = self
+            .marks
+            .iter()
+            .filter(move |mark| mark.line == line_index)
+            .map(|mark| (mark.range.clone(), Highlight::Mark(mark.severity)));
+
+        let matches = self
+            .matches
+            .iter()
+            .filter(move |m| m.line == line_index)
+            .map(|m| {
+                (
+                    m.range.clone(),
+                    Highlight::Match {
+                        is_current: m.is_current,
+                    },
+                )
+            });
+
         Box::new(
             ScopeRangeIterator {
                 ops,
@@ -136,9 +300,11 @@ impl iced::advanced::text::Highlighter for Highlighter {
                 } else {
                     let modifier = highlighter.style_mod_for_stack(&stack.scopes);
 
-                    Some((range, Highlight(modifier)))
+                    Some((range, Highlight::Syntax(modifier)))
                 }
-            }),
+            })
+            .chain(marks)
+            .chain(matches),
         )
     }
 
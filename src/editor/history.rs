@@ -0,0 +1,147 @@
+use crate::editor::cursor;
+use iced::widget::text_editor::{Action, Content, Edit};
+
+const MAX_ENTRIES: usize = 256;
+
+/// Undo/redo stacks of editor snapshots.
+///
+/// Shaders are small enough that storing the whole buffer per undo unit is cheaper than
+/// tracking inverse edits, and it keeps restoring trivially correct.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    group: Option<Group>,
+}
+
+struct Snapshot {
+    text: String,
+    cursor: usize,
+    anchor: Option<usize>,
+    is_valid: bool,
+}
+
+/// The run of edits that is currently being merged into a single undo unit.
+struct Group {
+    kind: Kind,
+    cursor: (usize, usize),
+    last: Option<char>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Insert,
+    Delete,
+}
+
+impl History {
+    /// Performs `edit` on `content`, merging it into the current undo unit when it continues
+    /// the previous edit (e.g. typing a word or holding backspace).
+    pub fn perform(&mut self, content: &mut Content, edit: Edit, is_valid: bool) {
+        let kind = match edit {
+            Edit::Insert(_) => Some(Kind::Insert),
+            Edit::Backspace | Edit::Delete => Some(Kind::Delete),
+            Edit::Paste(_) | Edit::Enter => None,
+        };
+        let ch = match edit {
+            Edit::Insert(ch) => Some(ch),
+            _ => None,
+        };
+
+        let continues_group = self.group.as_ref().is_some_and(|group| {
+            Some(group.kind) == kind
+                && group.cursor == content.cursor_position()
+                && content.selection().is_none()
+                && !is_word_boundary(group.last, ch)
+        });
+
+        if !continues_group {
+            self.checkpoint(content, is_valid);
+        }
+
+        content.perform(Action::Edit(edit));
+
+        self.group = kind.map(|kind| Group {
+            kind,
+            cursor: content.cursor_position(),
+            last: ch,
+        });
+    }
+
+    /// Records an undo point for a compound change (indentation, replace, ...) that the
+    /// caller is about to apply to `content` directly.
+    pub fn checkpoint(&mut self, content: &Content, is_valid: bool) {
+        if self.undo.len() == MAX_ENTRIES {
+            self.undo.remove(0);
+        }
+
+        self.undo.push(Snapshot::new(content, is_valid));
+        self.redo.clear();
+        self.group = None;
+    }
+
+    /// Restores the previous undo unit, returning whether it was a valid shader.
+    pub fn undo(&mut self, content: &mut Content, is_valid: bool) -> Option<bool> {
+        let snapshot = self.undo.pop()?;
+
+        self.redo.push(Snapshot::new(content, is_valid));
+        self.group = None;
+
+        Some(snapshot.restore(content))
+    }
+
+    /// Re-applies the last undone unit, returning whether it was a valid shader.
+    pub fn redo(&mut self, content: &mut Content, is_valid: bool) -> Option<bool> {
+        let snapshot = self.redo.pop()?;
+
+        self.undo.push(Snapshot::new(content, is_valid));
+        self.group = None;
+
+        Some(snapshot.restore(content))
+    }
+}
+
+impl Snapshot {
+    fn new(content: &Content, is_valid: bool) -> Self {
+        let text = content.text();
+        let cursor = cursor::offset(content, content.cursor_position());
+
+        // The editor only exposes the selected text, so work out which side of the cursor it
+        // lies on by comparing it against the buffer.
+        let anchor = content.selection().map(|selection| {
+            let len = selection.chars().count();
+            let before = text.chars().skip(cursor.saturating_sub(len)).take(len);
+
+            if cursor >= len && before.eq(selection.chars()) {
+                cursor - len
+            } else {
+                cursor + len
+            }
+        });
+
+        Self {
+            text,
+            cursor,
+            anchor,
+            is_valid,
+        }
+    }
+
+    fn restore(self, content: &mut Content) -> bool {
+        *content = Content::with_text(&self.text);
+
+        match self.anchor {
+            Some(anchor) => cursor::select(content, anchor, self.cursor),
+            None => cursor::move_to(content, self.cursor),
+        }
+
+        self.is_valid
+    }
+}
+
+fn is_word_boundary(last: Option<char>, next: Option<char>) -> bool {
+    match (last, next) {
+        (Some(last), Some(next)) => !last.is_whitespace() && next.is_whitespace(),
+        _ => false,
+    }
+}
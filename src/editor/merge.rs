@@ -0,0 +1,169 @@
+//! Line based three-way merge, used when a shader changes on disk while it has unsaved edits.
+
+const MINE: &str = "<<<<<<< mine\n";
+const SEPARATOR: &str = "=======\n";
+const THEIRS: &str = ">>>>>>> disk\n";
+
+pub struct Merged {
+    pub text: String,
+    pub conflicts: usize,
+}
+
+/// Merges the changes from `base` to `mine` and from `base` to `theirs`. Regions changed on
+/// both sides are kept with conflict markers around them.
+pub fn merge(base: &str, mine: &str, theirs: &str) -> Merged {
+    let base = lines(base);
+    let mine = lines(mine);
+    let theirs = lines(theirs);
+
+    let to_mine = matches(&base, &mine);
+    let to_theirs = matches(&base, &theirs);
+
+    let mut merged = Merged {
+        text: String::new(),
+        conflicts: 0,
+    };
+
+    let (mut o, mut a, mut b) = (0, 0, 0);
+
+    loop {
+        // Lines unchanged on both sides
+        while o < base.len() && to_mine[o] == Some(a) && to_theirs[o] == Some(b) {
+            merged.text.push_str(base[o]);
+            o += 1;
+            a += 1;
+            b += 1;
+        }
+
+        if o == base.len() && a == mine.len() && b == theirs.len() {
+            break;
+        }
+
+        // Find where both sides line up with the base again
+        let (next_o, next_a, next_b) = (o..base.len())
+            .find_map(|i| match (to_mine[i], to_theirs[i]) {
+                (Some(j), Some(k)) if j >= a && k >= b => Some((i, j, k)),
+                _ => None,
+            })
+            .unwrap_or((base.len(), mine.len(), theirs.len()));
+
+        let base_chunk = &base[o..next_o];
+        let mine_chunk = &mine[a..next_a];
+        let theirs_chunk = &theirs[b..next_b];
+
+        if mine_chunk == base_chunk || mine_chunk == theirs_chunk {
+            push_lines(&mut merged.text, theirs_chunk);
+        } else if theirs_chunk == base_chunk {
+            push_lines(&mut merged.text, mine_chunk);
+        } else {
+            merged.conflicts += 1;
+            merged.text.push_str(MINE);
+            push_lines(&mut merged.text, mine_chunk);
+            merged.text.push_str(SEPARATOR);
+            push_lines(&mut merged.text, theirs_chunk);
+            merged.text.push_str(THEIRS);
+        }
+
+        (o, a, b) = (next_o, next_a, next_b);
+    }
+
+    merged
+}
+
+/// Splits `text` into lines, making sure each one keeps its line break so conflict markers
+/// always start on a fresh line.
+fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+fn push_lines(text: &mut String, lines: &[&str]) {
+    for line in lines {
+        text.push_str(line);
+
+        if !line.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+}
+
+/// For each line of `base`, the index of the line it corresponds to in `other` according to
+/// their longest common subsequence.
+fn matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let width = other.len() + 1;
+    let mut lengths = vec![0u32; (base.len() + 1) * width];
+
+    for i in (0..base.len()).rev() {
+        for j in (0..other.len()).rev() {
+            lengths[i * width + j] = if base[i] == other[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut matched = vec![None; base.len()];
+    let (mut i, mut j) = (0, 0);
+
+    while i < base.len() && j < other.len() {
+        if base[i] == other[j] {
+            matched[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separate_edits_merge_cleanly() {
+        let merged = merge("a\nb\nc\nd\n", "A\nb\nc\nd\n", "a\nb\nc\nD\n");
+
+        assert_eq!(merged.text, "A\nb\nc\nD\n");
+        assert_eq!(merged.conflicts, 0);
+    }
+
+    #[test]
+    fn same_edit_on_both_sides_is_kept_once() {
+        let merged = merge("a\nb\nc\n", "a\nB\nc\n", "a\nB\nc\n");
+
+        assert_eq!(merged.text, "a\nB\nc\n");
+        assert_eq!(merged.conflicts, 0);
+    }
+
+    #[test]
+    fn conflicting_edits_are_marked() {
+        let merged = merge("a\nb\nc\n", "a\nmine\nc\n", "a\ntheirs\nc\n");
+
+        assert_eq!(
+            merged.text,
+            format!("a\n{MINE}mine\n{SEPARATOR}theirs\n{THEIRS}c\n")
+        );
+        assert_eq!(merged.conflicts, 1);
+    }
+
+    #[test]
+    fn insertions_at_the_start_and_end() {
+        let merged = merge("a\nb\n", "start\na\nb\n", "a\nb\nend\n");
+
+        assert_eq!(merged.text, "start\na\nb\nend\n");
+        assert_eq!(merged.conflicts, 0);
+    }
+
+    #[test]
+    fn theirs_without_a_trailing_newline() {
+        let merged = merge("a\nb\nc\n", "A\nb\nc\n", "a\nb\nC");
+
+        assert_eq!(merged.text, "A\nb\nC\n");
+        assert_eq!(merged.conflicts, 0);
+    }
+}
@@ -0,0 +1,333 @@
+use crate::editor::params;
+use crate::editor::settings::setting;
+use crate::editor::validation::EntryPoints;
+use crate::editor::{Element, Message, icon};
+use crate::viewer::{Filter, Wrap};
+use crate::{BUFFERS, CHANNELS};
+use iced::Alignment;
+use iced::widget::{button, checkbox, column, pick_list, row, text, text_input};
+use lucide_icons::Icon;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const BUFFER_NAMES: [&str; BUFFERS] = ["Buffer A", "Buffer B", "Buffer C", "Buffer D"];
+const CHANNEL_NAMES: [&str; CHANNELS] = ["channel0", "channel1", "channel2", "channel3"];
+
+/// The most vertices or instances a draw can ask for, so a typo can't hang the GPU.
+const MAX_COUNT: u32 = 1_000_000;
+
+/// How a file's shader is run, remembered per file.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// The fragment entry point the user picked, the default one is used until they do.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_point: Option<String>,
+    /// Set when the shader brings its own vertex stage instead of the full-screen quad.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertex: Option<Vertex>,
+    /// The fragment entry point rendering to each buffer, the shader is single pass without any.
+    #[serde(skip_serializing_if = "is_single_pass")]
+    pub buffers: [Option<String>; BUFFERS],
+    /// The texture each channel samples, if any.
+    #[serde(skip_serializing_if = "has_no_channels")]
+    pub channels: [Option<Channel>; CHANNELS],
+    /// Values for the shader's params, kept for ones it no longer declares in case they come back.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: params::Values,
+}
+
+fn is_single_pass(buffers: &[Option<String>; BUFFERS]) -> bool {
+    buffers.iter().all(Option::is_none)
+}
+
+fn has_no_channels(channels: &[Option<Channel>; CHANNELS]) -> bool {
+    channels.iter().all(Option::is_none)
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Channel {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub filter: Filter,
+    #[serde(default)]
+    pub wrap: Wrap,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Vertex {
+    /// Like [`Options::entry_point`], for the vertex stage.
+    pub entry_point: Option<String>,
+    pub vertex_count: u32,
+    pub instance_count: u32,
+}
+
+impl Default for Vertex {
+    /// Enough for a quad, so the built-in vertex shader can be pasted in as a starting point.
+    fn default() -> Self {
+        Self {
+            entry_point: None,
+            vertex_count: 6,
+            instance_count: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Change {
+    EntryPoint(String),
+    CustomVertex(bool),
+    VertexEntryPoint(String),
+    /// Counts come straight from a text field and are parsed when applied.
+    VertexCount(String),
+    InstanceCount(String),
+    /// Renders a buffer with an entry point, or stops rendering it.
+    Buffer(usize, Option<String>),
+    /// Samples a texture from disk in a channel, or clears it.
+    Channel(usize, Option<PathBuf>),
+    ChannelFilter(usize, Filter),
+    ChannelWrap(usize, Wrap),
+}
+
+impl Options {
+    pub fn has_buffers(&self) -> bool {
+        !is_single_pass(&self.buffers)
+    }
+
+    pub fn channels_used(&self) -> [bool; CHANNELS] {
+        self.channels.each_ref().map(Option::is_some)
+    }
+
+    /// Every texture the channels sample.
+    pub fn images(&self) -> impl Iterator<Item = &PathBuf> {
+        self.channels.iter().flatten().map(|channel| &channel.path)
+    }
+
+    pub fn apply(&mut self, change: Change) {
+        match change {
+            Change::EntryPoint(entry_point) => self.entry_point = Some(entry_point),
+            Change::CustomVertex(is_custom) => {
+                self.vertex = is_custom.then(Vertex::default);
+            }
+            Change::VertexEntryPoint(entry_point) => {
+                if let Some(vertex) = &mut self.vertex {
+                    vertex.entry_point = Some(entry_point);
+                }
+            }
+            Change::VertexCount(count) => {
+                if let Some(vertex) = &mut self.vertex {
+                    vertex.vertex_count = parse_count(&count).unwrap_or(vertex.vertex_count);
+                }
+            }
+            Change::InstanceCount(count) => {
+                if let Some(vertex) = &mut self.vertex {
+                    vertex.instance_count = parse_count(&count).unwrap_or(vertex.instance_count);
+                }
+            }
+            Change::Buffer(buffer, entry_point) => {
+                if let Some(slot) = self.buffers.get_mut(buffer) {
+                    *slot = entry_point;
+                }
+            }
+            Change::Channel(channel, path) => {
+                if let Some(slot) = self.channels.get_mut(channel) {
+                    // Swapping the texture keeps how it's sampled
+                    *slot = path.map(|path| match slot.take() {
+                        Some(previous) => Channel { path, ..previous },
+                        None => Channel {
+                            path,
+                            filter: Filter::default(),
+                            wrap: Wrap::default(),
+                        },
+                    });
+                }
+            }
+            Change::ChannelFilter(channel, filter) => {
+                if let Some(Some(channel)) = self.channels.get_mut(channel) {
+                    channel.filter = filter;
+                }
+            }
+            Change::ChannelWrap(channel, wrap) => {
+                if let Some(Some(channel)) = self.channels.get_mut(channel) {
+                    channel.wrap = wrap;
+                }
+            }
+        }
+    }
+
+    /// `vertex_entry_point` is the vertex entry point in use, if there's a custom one.
+    pub fn view<'a>(
+        &'a self,
+        entry_points: &'a EntryPoints,
+        vertex_entry_point: Option<String>,
+    ) -> Element<'a, Message> {
+        column![
+            text("Pipeline").size(20),
+            self.passes(&entry_points.fragment),
+            self.channels(),
+            self.geometry(&entry_points.vertex, vertex_entry_point),
+        ]
+        .spacing(20)
+        .width(340)
+        .into()
+    }
+
+    fn passes<'a>(&'a self, entry_points: &'a [String]) -> Element<'a, Message> {
+        let buffers =
+            self.buffers
+                .iter()
+                .zip(BUFFER_NAMES)
+                .enumerate()
+                .map(|(i, (entry_point, name))| {
+                    let picker = pick_list(entry_points, entry_point.clone(), move |entry_point| {
+                        Message::ChangeOptions(Change::Buffer(i, Some(entry_point)))
+                    })
+                    .placeholder("Unused")
+                    .width(160);
+
+                    let clear = button(icon(Icon::X)).on_press_maybe(
+                        entry_point
+                            .is_some()
+                            .then_some(Message::ChangeOptions(Change::Buffer(i, None))),
+                    );
+
+                    setting(
+                        name,
+                        row![picker, clear].spacing(5).align_y(Alignment::Center),
+                    )
+                });
+
+        let mut content = column![text("Passes")].extend(buffers).spacing(10);
+
+        if self.has_buffers() {
+            content = content
+                .push(text(
+                    "Buffers see the previous frame, the image sees this one.",
+                ))
+                .push(button(text("Reset buffers")).on_press(Message::ResetBuffers));
+        }
+
+        content.into()
+    }
+
+    fn channels(&self) -> Element<'_, Message> {
+        let channels = self
+            .channels
+            .iter()
+            .zip(CHANNEL_NAMES)
+            .enumerate()
+            .map(|(i, (picked, name))| channel(i, name, picked.as_ref()));
+
+        column![text("Channels")]
+            .extend(channels)
+            .push(text(
+                "PNG, JPEG and HDR images, sampled with channelN_sampler.",
+            ))
+            .spacing(10)
+            .into()
+    }
+
+    fn geometry<'a>(
+        &'a self,
+        entry_points: &'a [String],
+        selected: Option<String>,
+    ) -> Element<'a, Message> {
+        let mut content = column![
+            text("Geometry"),
+            setting(
+                "Custom vertex stage",
+                checkbox("", self.vertex.is_some())
+                    .on_toggle(|is_custom| Message::ChangeOptions(Change::CustomVertex(is_custom))),
+            ),
+        ]
+        .spacing(10);
+
+        let Some(vertex) = &self.vertex else {
+            return content
+                .push(text("The shader is drawn over a full-screen quad."))
+                .into();
+        };
+
+        content = content
+            .push(setting(
+                "Entry point",
+                pick_list(entry_points, selected, |entry_point| {
+                    Message::ChangeOptions(Change::VertexEntryPoint(entry_point))
+                })
+                .placeholder("No @vertex functions"),
+            ))
+            .push(setting(
+                "Vertices",
+                count(vertex.vertex_count, |count| {
+                    Message::ChangeOptions(Change::VertexCount(count))
+                }),
+            ))
+            .push(setting(
+                "Instances",
+                count(vertex.instance_count, |count| {
+                    Message::ChangeOptions(Change::InstanceCount(count))
+                }),
+            ));
+
+        content.into()
+    }
+}
+
+fn channel<'a>(i: usize, name: &'a str, picked: Option<&Channel>) -> Element<'a, Message> {
+    let file_name = picked
+        .and_then(|picked| picked.path.file_name())
+        .map_or_else(
+            || "Pick an image...".to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+
+    let pick = button(text(file_name).wrapping(text::Wrapping::None))
+        .on_press(Message::PickChannel(i))
+        .width(160);
+
+    let clear = button(icon(Icon::X)).on_press_maybe(
+        picked
+            .is_some()
+            .then_some(Message::ChangeOptions(Change::Channel(i, None))),
+    );
+
+    let mut content = column![setting(
+        name,
+        row![pick, clear].spacing(5).align_y(Alignment::Center),
+    )]
+    .spacing(5);
+
+    if let Some(picked) = picked {
+        content = content.push(
+            row![
+                pick_list(Filter::ALL, Some(picked.filter), move |filter| {
+                    Message::ChangeOptions(Change::ChannelFilter(i, filter))
+                }),
+                pick_list(Wrap::ALL, Some(picked.wrap), move |wrap| {
+                    Message::ChangeOptions(Change::ChannelWrap(i, wrap))
+                }),
+            ]
+            .spacing(5),
+        );
+    }
+
+    content.into()
+}
+
+fn count<'a>(value: u32, on_input: fn(String) -> Message) -> Element<'a, Message> {
+    text_input("0", &value.to_string())
+        .on_input(on_input)
+        .width(100)
+        .into()
+}
+
+/// Clearing the field counts as zero so a new number can be typed in.
+fn parse_count(count: &str) -> Option<u32> {
+    if count.is_empty() {
+        return Some(0);
+    }
+
+    count.parse::<u32>().ok().map(|count| count.min(MAX_COUNT))
+}
@@ -0,0 +1,435 @@
+//! Parameters a shader exposes for tweaking, read from the uniform it declares for them:
+//!
+//! ```wgsl
+//! struct Params {
+//!     speed: f32,      // @range(0, 10)
+//!     tint: vec3<f32>, // @color
+//!     center: vec2<f32>,
+//!     mirror: u32,     // @toggle
+//! }
+//!
+//! @group(0) @binding(1) var<uniform> params: Params;
+//! ```
+
+use crate::editor::{Element, Message};
+use crate::theme::ContainerClass;
+use iced::widget::{Space, button, checkbox, column, container, mouse_area, row, slider, text};
+use iced::{Alignment, Color, Length, Padding, Point};
+use naga::{AddressSpace, ResourceBinding, ScalarKind, TypeInner};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+/// Where shaders bind their params, next to the built-in uniforms.
+pub const BINDING: ResourceBinding = ResourceBinding {
+    group: 0,
+    binding: 1,
+};
+
+/// How big a pad is, and the dot on it.
+const PAD_SIZE: f32 = 120.0;
+const DOT_SIZE: f32 = 8.0;
+
+/// The value of each param by name, which outlives shader edits that move params around.
+pub type Values = BTreeMap<String, Vec<f32>>;
+
+/// The params a shader declares, laid out the way WGSL lays out uniforms.
+#[derive(Clone, Debug, Default)]
+pub struct Layout {
+    /// The size of the uniform buffer.
+    pub size: u64,
+    pub params: Vec<Param>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Param {
+    pub name: String,
+    offset: u32,
+    kind: Kind,
+    /// One for scalars, the size for vectors.
+    components: usize,
+    control: Control,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    Float,
+    Sint,
+    Uint,
+}
+
+#[derive(Clone, Debug)]
+enum Control {
+    /// A slider for each component.
+    Sliders(RangeInclusive<f32>),
+    Toggle,
+    /// Red, green, blue and maybe alpha sliders, along with the color they make.
+    Color,
+    /// For a `vec2<f32>`, which can be dragged around in a square.
+    Pad(RangeInclusive<f32>),
+}
+
+/// Finds the params `module` declares, if it has any. `source` is read for the comments that pick
+/// controls, which naga drops.
+pub fn reflect(module: &naga::Module, source: &str) -> Option<Layout> {
+    let (_, variable) = module.global_variables.iter().find(|(_, variable)| {
+        variable.space == AddressSpace::Uniform && variable.binding == Some(BINDING)
+    })?;
+
+    let ty = &module.types[variable.ty];
+
+    // Usually a struct, but a single value works too
+    let fields: Vec<_> = match &ty.inner {
+        TypeInner::Struct { members, .. } => members
+            .iter()
+            .filter_map(|member| Some((member.name.clone()?, member.ty, member.offset)))
+            .collect(),
+        _ => vec![(variable.name.clone().unwrap_or_default(), variable.ty, 0)],
+    };
+
+    let annotations = ty
+        .name
+        .as_deref()
+        .map(|name| annotations(source, name))
+        .unwrap_or_default();
+
+    let params = fields
+        .into_iter()
+        .filter_map(|(name, ty, offset)| {
+            let (scalar, components) = match module.types[ty].inner {
+                TypeInner::Scalar(scalar) => (scalar, 1),
+                TypeInner::Vector { size, scalar } => (scalar, size as usize),
+                // Matrices, arrays and nested structs are left zeroed
+                _ => return None,
+            };
+
+            let kind = match scalar.kind {
+                ScalarKind::Float if scalar.width == 4 => Kind::Float,
+                ScalarKind::Sint if scalar.width == 4 => Kind::Sint,
+                ScalarKind::Uint if scalar.width == 4 => Kind::Uint,
+                _ => return None,
+            };
+
+            let annotation = annotations.get(name.as_str()).copied().unwrap_or("");
+
+            Some(Param {
+                control: Control::new(annotation, kind, components),
+                name,
+                offset,
+                kind,
+                components,
+            })
+        })
+        .collect();
+
+    Some(Layout {
+        size: ty.inner.size(module.to_ctx()) as u64,
+        params,
+    })
+}
+
+/// The comment after each field of `struct name`, like `// @range(0, 10)` for `speed`.
+fn annotations<'a>(source: &'a str, name: &str) -> HashMap<&'a str, &'a str> {
+    let declaration = format!("struct {name}");
+
+    // Skip structs whose name only starts with `name`
+    let Some(start) = source.match_indices(&declaration).find_map(|(start, _)| {
+        let after = source[start + declaration.len()..].chars().next()?;
+        (after.is_whitespace() || after == '{').then_some(start)
+    }) else {
+        return HashMap::new();
+    };
+
+    let mut annotations = HashMap::new();
+
+    for line in source[start..].lines().skip(1) {
+        let (code, comment) = line.split_once("//").unwrap_or((line, ""));
+
+        if let Some((field, _)) = code.split_once(':') {
+            annotations.insert(field.trim(), comment.trim());
+        }
+
+        if code.contains('}') {
+            break;
+        }
+    }
+
+    annotations
+}
+
+impl Control {
+    fn new(annotation: &str, kind: Kind, components: usize) -> Self {
+        let is_float = matches!(kind, Kind::Float);
+
+        let range =
+            parse_range(annotation).unwrap_or(if is_float { 0.0..=1.0 } else { 0.0..=10.0 });
+
+        if annotation.contains("@toggle") && components == 1 {
+            Control::Toggle
+        } else if annotation.contains("@color") && is_float && components >= 3 {
+            Control::Color
+        } else if is_float && components == 2 {
+            Control::Pad(range)
+        } else {
+            Control::Sliders(range)
+        }
+    }
+}
+
+/// Reads `@range(min, max)`.
+fn parse_range(annotation: &str) -> Option<RangeInclusive<f32>> {
+    let (_, rest) = annotation.split_once("@range(")?;
+    let (range, _) = rest.split_once(')')?;
+    let (min, max) = range.split_once(',')?;
+
+    let (min, max) = (
+        min.trim().parse::<f32>().ok()?,
+        max.trim().parse::<f32>().ok()?,
+    );
+
+    (min < max).then_some(min..=max)
+}
+
+impl Param {
+    /// What a param starts out as: white for colors, otherwise zero or as close as the range
+    /// allows.
+    fn default_value(&self) -> Vec<f32> {
+        let value = match &self.control {
+            Control::Color => 1.0,
+            Control::Toggle => 0.0,
+            Control::Sliders(range) | Control::Pad(range) => {
+                0.0_f32.clamp(*range.start(), *range.end())
+            }
+        };
+
+        vec![value; self.components]
+    }
+
+    /// The param's value in `values`, unless it's missing or was saved for a different type.
+    fn value(&self, values: &Values) -> Vec<f32> {
+        values
+            .get(&self.name)
+            .filter(|value| value.len() == self.components)
+            .cloned()
+            .unwrap_or_else(|| self.default_value())
+    }
+
+    fn view<'a>(&'a self, value: Vec<f32>) -> Element<'a, Message> {
+        let change = move |value| Message::ChangeParam(self.name.clone(), value);
+
+        match &self.control {
+            Control::Toggle => checkbox(self.name.as_str(), value[0] != 0.0)
+                .on_toggle(move |is_on| change(vec![if is_on { 1.0 } else { 0.0 }]))
+                .into(),
+            Control::Sliders(range) => {
+                let label = value
+                    .iter()
+                    .map(|component| self.format(*component))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                let step = match self.kind {
+                    Kind::Float => (range.end() - range.start()) / 1000.0,
+                    Kind::Sint | Kind::Uint => 1.0,
+                };
+
+                column![labelled(&self.name, label)]
+                    .extend(sliders(&value, range, step, change))
+                    .spacing(5)
+                    .into()
+            }
+            Control::Color => {
+                let color = Color::from_rgba(
+                    value[0],
+                    value[1],
+                    value[2],
+                    value.get(3).copied().unwrap_or(1.0),
+                );
+
+                let swatch = container(Space::new(24, 24)).class(ContainerClass::Swatch(color));
+
+                column![
+                    row![text(self.name.as_str()).width(Length::Fill), swatch]
+                        .align_y(Alignment::Center)
+                ]
+                .extend(sliders(&value, &(0.0..=1.0), 0.001, change))
+                .spacing(5)
+                .into()
+            }
+            Control::Pad(range) => {
+                let label = format!("{:.3}, {:.3}", value[0], value[1]);
+                let (start, length) = (*range.start(), range.end() - range.start());
+
+                // Up is positive, like the y axis usually is in shader math
+                let travel = PAD_SIZE - DOT_SIZE;
+                let x = (value[0] - start) / length * travel;
+                let y = (1.0 - (value[1] - start) / length) * travel;
+
+                let dot = container(Space::new(DOT_SIZE, DOT_SIZE))
+                    .class(ContainerClass::Swatch(Color::WHITE));
+
+                let pad = container(dot)
+                    .padding(Padding {
+                        top: y,
+                        left: x,
+                        ..Padding::ZERO
+                    })
+                    .width(PAD_SIZE)
+                    .height(PAD_SIZE)
+                    .class(ContainerClass::Pad);
+
+                let name = self.name.clone();
+                let pad = mouse_area(pad)
+                    .on_move(move |point: Point| {
+                        let x = (point.x / PAD_SIZE).clamp(0.0, 1.0);
+                        let y = 1.0 - (point.y / PAD_SIZE).clamp(0.0, 1.0);
+
+                        Message::Pad(Pad::Moved(
+                            name.clone(),
+                            vec![start + x * length, start + y * length],
+                        ))
+                    })
+                    .on_press(Message::Pad(Pad::Pressed))
+                    .on_release(Message::Pad(Pad::Released))
+                    .on_exit(Message::Pad(Pad::Exited));
+
+                column![labelled(&self.name, label), pad].spacing(5).into()
+            }
+        }
+    }
+
+    fn format(&self, value: f32) -> String {
+        match self.kind {
+            Kind::Float => format!("{value:.3}"),
+            Kind::Sint | Kind::Uint => format!("{}", value.round()),
+        }
+    }
+}
+
+fn labelled<'a>(name: &'a str, value: String) -> Element<'a, Message> {
+    row![text(name).width(Length::Fill), text(value)]
+        .spacing(10)
+        .into()
+}
+
+/// A slider per component of `value`. Preferences are saved once one is let go of, not for every
+/// step on the way.
+fn sliders<'a>(
+    value: &[f32],
+    range: &RangeInclusive<f32>,
+    step: f32,
+    change: impl Fn(Vec<f32>) -> Message + Copy + 'a,
+) -> Vec<Element<'a, Message>> {
+    (0..value.len())
+        .map(|i| {
+            let value = value.to_vec();
+
+            slider(range.clone(), value[i], move |component| {
+                let mut value = value.clone();
+                value[i] = component;
+                change(value)
+            })
+            .step(step)
+            .on_release(Message::SavePreferences)
+            .into()
+        })
+        .collect()
+}
+
+impl Layout {
+    /// The uniform buffer's contents with `values` in place.
+    pub fn pack(&self, values: &Values) -> Vec<u8> {
+        let mut bytes = vec![0; self.size as usize];
+
+        for param in &self.params {
+            for (i, component) in param.value(values).into_iter().enumerate() {
+                let word = match param.kind {
+                    Kind::Float => component.to_bits(),
+                    Kind::Sint => (component as i32) as u32,
+                    Kind::Uint => component as u32,
+                };
+
+                let start = param.offset as usize + i * 4;
+                bytes[start..start + 4].copy_from_slice(&word.to_ne_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    pub fn view<'a>(&'a self, values: &Values) -> Element<'a, Message> {
+        let params = self
+            .params
+            .iter()
+            .map(|param| param.view(param.value(values)));
+
+        let reset = button(text("Reset to defaults"))
+            .on_press_maybe((!values.is_empty()).then_some(Message::ResetParams));
+
+        column![text("Parameters").size(20)]
+            .extend(params)
+            .push(reset)
+            .spacing(15)
+            .width(300)
+            .into()
+    }
+}
+
+/// Shown while the shader doesn't declare params.
+pub fn placeholder<'a>() -> Element<'a, Message> {
+    column![
+        text("Parameters").size(20),
+        text(
+            "Declare `@group(0) @binding(1) var<uniform> params: Params;` to get controls for \
+             the fields of `Params`. A comment after a field like `// @range(0, 10)`, \
+             `// @color` or `// @toggle` picks its control."
+        ),
+    ]
+    .spacing(15)
+    .width(300)
+    .into()
+}
+
+/// Mouse events over a pad.
+#[derive(Clone, Debug)]
+pub enum Pad {
+    /// The value of the named param under the mouse.
+    Moved(String, Vec<f32>),
+    Pressed,
+    Released,
+    Exited,
+}
+
+/// Follows the mouse over pads, since pressing one doesn't say where.
+#[derive(Debug, Default)]
+pub struct Drag {
+    hovered: Option<(String, Vec<f32>)>,
+    is_dragging: bool,
+}
+
+impl Drag {
+    /// The change to make to a param, if there is one.
+    pub fn update(&mut self, event: Pad) -> Option<(String, Vec<f32>)> {
+        match event {
+            Pad::Moved(name, value) => {
+                self.hovered = Some((name, value));
+
+                self.is_dragging.then(|| self.hovered.clone()).flatten()
+            }
+            Pad::Pressed => {
+                self.is_dragging = true;
+
+                self.hovered.clone()
+            }
+            Pad::Released => {
+                self.is_dragging = false;
+
+                None
+            }
+            Pad::Exited => {
+                *self = Self::default();
+
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::viewer::Device;
+use iced::widget::shader::wgpu::{Features, Limits};
+use naga::valid::Capabilities;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// What shaders are validated against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Profile {
+    /// What the viewer's device was created with. iced doesn't ask for any of the adapter's
+    /// optional features, so shaders can't use them here even where the adapter has them, but
+    /// the device's limits can be tighter than WebGPU's.
+    #[default]
+    Device,
+    /// Only what every WebGPU implementation has to support, for shaders meant to run anywhere.
+    WebGpu,
+    /// Everything naga knows about, regardless of what this machine can run.
+    Native,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 3] = [Profile::Device, Profile::WebGpu, Profile::Native];
+
+    /// Until the viewer has reported its device, it's assumed to be a WebGPU baseline one.
+    pub fn target(&self, device: Option<&Device>) -> Target {
+        match (self, device) {
+            (Profile::Device, Some(device)) => Target {
+                capabilities: from_features(device.features),
+                limits: Some(device.limits.clone()),
+            },
+            (Profile::Device | Profile::WebGpu, _) => Target {
+                capabilities: Capabilities::default(),
+                limits: Some(Limits::default()),
+            },
+            (Profile::Native, _) => Target {
+                capabilities: Capabilities::all(),
+                limits: None,
+            },
+        }
+    }
+}
+
+/// What a profile lets shaders use.
+#[derive(Clone, Debug)]
+pub struct Target {
+    pub capabilities: Capabilities,
+    /// Unset when shaders aren't held to any.
+    pub limits: Option<Limits>,
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Profile::Device => "This device",
+            Profile::WebGpu => "WebGPU baseline",
+            Profile::Native => "Native (everything)",
+        })
+    }
+}
+
+/// Mirrors how wgpu sets up its own validator when creating shader modules. The device doesn't
+/// expose downlevel flags, so the ones WebGPU requires are assumed.
+fn from_features(features: Features) -> Capabilities {
+    let mut capabilities = Capabilities::default();
+
+    let mapping = [
+        (Features::PUSH_CONSTANTS, Capabilities::PUSH_CONSTANT),
+        (Features::SHADER_F64, Capabilities::FLOAT64),
+        (
+            Features::SHADER_PRIMITIVE_INDEX,
+            Capabilities::PRIMITIVE_INDEX,
+        ),
+        (
+            Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+            Capabilities::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
+                | Capabilities::SAMPLER_NON_UNIFORM_INDEXING,
+        ),
+        (
+            Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+            Capabilities::UNIFORM_BUFFER_ARRAY_NON_UNIFORM_INDEXING
+                | Capabilities::STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+        ),
+        (
+            Features::TEXTURE_FORMAT_16BIT_NORM,
+            Capabilities::STORAGE_TEXTURE_16BIT_NORM_FORMATS,
+        ),
+        (Features::MULTIVIEW, Capabilities::MULTIVIEW),
+        (
+            Features::SHADER_EARLY_DEPTH_TEST,
+            Capabilities::EARLY_DEPTH_TEST,
+        ),
+        (Features::RAY_QUERY, Capabilities::RAY_QUERY),
+        (
+            Features::DUAL_SOURCE_BLENDING,
+            Capabilities::DUAL_SOURCE_BLENDING,
+        ),
+    ];
+
+    for (feature, capability) in mapping {
+        capabilities.set(capability, features.contains(feature));
+    }
+
+    capabilities
+}
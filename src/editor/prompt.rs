@@ -0,0 +1,57 @@
+use crate::FragmentShader;
+use crate::editor::{Element, Message, tab};
+use crate::theme::ContainerClass;
+use iced::widget::{button, center, column, container, mouse_area, opaque, row, text};
+use iced::{Alignment, Length};
+use std::sync::Arc;
+
+/// An action that would throw away unsaved changes, waiting on the user to confirm it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pending {
+    CloseTab(tab::Id),
+    Exit,
+    /// The tab's file was changed by another program, these are its new contents.
+    Changed(tab::Id, Arc<FragmentShader>),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Choice {
+    Save,
+    Discard,
+    Merge,
+    Cancel,
+}
+
+pub fn view<'a>(
+    title: &'a str,
+    description: String,
+    choices: &[(&'a str, Choice)],
+) -> Element<'a, Message> {
+    let buttons = row(choices.iter().map(|(label, choice)| {
+        button(text(*label))
+            .on_press(Message::Confirm(*choice))
+            .into()
+    }))
+    .spacing(10);
+
+    let dialog = container(
+        column![
+            text(title).size(20),
+            text(description),
+            container(buttons)
+                .width(Length::Fill)
+                .align_right(Length::Fill),
+        ]
+        .spacing(15)
+        .align_x(Alignment::Start),
+    )
+    .width(420)
+    .padding(20)
+    .class(ContainerClass::Dialog);
+
+    // Swallow clicks outside of the dialog so the editor can't change underneath it
+    opaque(
+        mouse_area(center(opaque(dialog)).class(ContainerClass::Backdrop))
+            .on_press(Message::Confirm(Choice::Cancel)),
+    )
+}
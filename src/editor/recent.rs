@@ -0,0 +1,122 @@
+use crate::editor::{Element, Message};
+use crate::theme::{ButtonClass, TextClass};
+use iced::widget::{button, column, row, text};
+use iced::{Alignment, Length};
+use std::path::{Path, PathBuf};
+
+/// How many files are remembered before the oldest ones are forgotten.
+const CAPACITY: usize = 10;
+
+/// Recently opened or saved shaders, most recent first.
+#[derive(Default)]
+pub struct Recent {
+    pub is_open: bool,
+    files: Vec<PathBuf>,
+    /// Files that couldn't be found the last time the list was opened.
+    missing: Vec<PathBuf>,
+}
+
+impl Recent {
+    pub fn new(mut files: Vec<PathBuf>) -> Self {
+        // The preferences file could have been edited by hand
+        let mut seen = Vec::new();
+        files.retain(|path| {
+            let is_new = !seen.contains(path);
+            seen.push(path.clone());
+            is_new
+        });
+        files.truncate(CAPACITY);
+
+        Self {
+            files,
+            ..Self::default()
+        }
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Moves `path` to the top of the list.
+    pub fn push(&mut self, path: PathBuf) {
+        self.files.retain(|file| *file != path);
+        self.missing.retain(|file| *file != path);
+        self.files.insert(0, path);
+        self.files.truncate(CAPACITY);
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.files.retain(|file| file != path);
+        self.missing.retain(|file| file != path);
+    }
+
+    pub fn set_missing(&mut self, missing: Vec<PathBuf>) {
+        self.missing = missing;
+    }
+
+    /// Forgets all files that no longer exist.
+    pub fn prune(&mut self) {
+        let missing = std::mem::take(&mut self.missing);
+        self.files.retain(|file| !missing.contains(file));
+    }
+
+    pub fn view(&'_ self) -> Element<'_, Message> {
+        let entries = if self.files.is_empty() {
+            column![text("No recent files").class(TextClass::Disabled)]
+        } else {
+            column(self.files.iter().map(|path| self.entry(path)))
+        }
+        .spacing(2);
+
+        let mut menu = column![entries].spacing(5).width(320);
+
+        if !self.missing.is_empty() {
+            menu = menu.push(
+                button(text("Remove missing files"))
+                    .class(ButtonClass::MenuItem)
+                    .width(Length::Fill)
+                    .on_press(Message::PruneRecent),
+            );
+        }
+
+        menu.into()
+    }
+
+    fn entry<'a>(&self, path: &'a Path) -> Element<'a, Message> {
+        let name = path
+            .file_name()
+            .map_or(String::new(), |name| name.to_string_lossy().to_string());
+        let directory = path
+            .parent()
+            .map_or(String::new(), |parent| parent.to_string_lossy().to_string());
+
+        if self.missing.iter().any(|file| file == path) {
+            let label = column![
+                text(format!("{name} (missing)")).class(TextClass::Disabled),
+                text(directory).size(12).class(TextClass::Disabled),
+            ];
+
+            row![
+                button(label)
+                    .class(ButtonClass::MenuItem)
+                    .width(Length::Fill),
+                button(text("Remove"))
+                    .class(ButtonClass::MenuItem)
+                    .on_press(Message::RemoveRecent(path.to_owned())),
+            ]
+            .align_y(Alignment::Center)
+            .into()
+        } else {
+            let label = column![
+                text(name),
+                text(directory).size(12).class(TextClass::Disabled)
+            ];
+
+            button(label)
+                .class(ButtonClass::MenuItem)
+                .width(Length::Fill)
+                .on_press(Message::OpenFile(path.to_owned()))
+                .into()
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use crate::editor::highlighter::{Lines, Match};
+use crate::editor::{Element, Message, control_button, icon, toggle_button};
+use crate::theme::{ContainerClass, TextClass};
+use iced::widget::{column, container, row, text, text_input};
+use iced::{Alignment, Length};
+use lucide_icons::Icon;
+use once_cell::sync::Lazy;
+use std::ops::Range;
+use syntect::parsing::{Regex, Region};
+
+pub static QUERY_ID: Lazy<text_input::Id> = Lazy::new(text_input::Id::unique);
+
+#[derive(Clone, Copy, Debug)]
+pub enum Toggle {
+    CaseSensitive,
+    WholeWord,
+    Regex,
+}
+
+/// State of the find/replace bar. Matches are byte ranges into [`text_editor::Content::text`].
+///
+/// [`text_editor::Content::text`]: iced::widget::text_editor::Content::text
+#[derive(Default)]
+pub struct Search {
+    pub is_open: bool,
+    pub query: String,
+    pub replacement: String,
+    case_sensitive: bool,
+    whole_word: bool,
+    regex: bool,
+    matches: Vec<Range<usize>>,
+    current: Option<usize>,
+    error: Option<String>,
+}
+
+impl Search {
+    pub fn toggle(&mut self, toggle: Toggle) {
+        let value = match toggle {
+            Toggle::CaseSensitive => &mut self.case_sensitive,
+            Toggle::WholeWord => &mut self.whole_word,
+            Toggle::Regex => &mut self.regex,
+        };
+
+        *value = !*value;
+    }
+
+    /// Re-runs the query against `text`, keeping the current match index where possible.
+    pub fn find(&mut self, text: &str) {
+        self.matches.clear();
+        self.error = None;
+
+        if self.query.is_empty() {
+            self.current = None;
+            return;
+        }
+
+        match self.pattern() {
+            Ok(regex) => {
+                let mut region = Region::new();
+                let mut start = 0;
+
+                while start <= text.len()
+                    && regex.search(text, start, text.len(), Some(&mut region))
+                {
+                    let Some((begin, end)) = region.pos(0) else {
+                        break;
+                    };
+
+                    self.matches.push(begin..end);
+
+                    // Step over empty matches so `^` or `\b` don't loop forever
+                    start = if end > begin {
+                        end
+                    } else {
+                        end + text[end..].chars().next().map_or(1, char::len_utf8)
+                    };
+                }
+            }
+            Err(error) => self.error = Some(error),
+        }
+
+        self.current = self
+            .current
+            .filter(|_| !self.matches.is_empty())
+            .map(|current| current.min(self.matches.len() - 1));
+    }
+
+    /// Selects the first match starting at or after the byte offset `from`, wrapping around.
+    pub fn next(&mut self, from: usize) -> Option<Range<usize>> {
+        let index = self
+            .matches
+            .iter()
+            .position(|range| range.start >= from)
+            .or((!self.matches.is_empty()).then_some(0))?;
+
+        self.current = Some(index);
+        self.matches.get(index).cloned()
+    }
+
+    /// Selects the last match ending before the byte offset `from`, wrapping around.
+    pub fn previous(&mut self, from: usize) -> Option<Range<usize>> {
+        let index = self
+            .matches
+            .iter()
+            .rposition(|range| range.end < from)
+            .or(self.matches.len().checked_sub(1))?;
+
+        self.current = Some(index);
+        self.matches.get(index).cloned()
+    }
+
+    pub fn current(&self) -> Option<Range<usize>> {
+        self.current
+            .and_then(|index| self.matches.get(index).cloned())
+    }
+
+    /// Returns the range of the current match along with the text to replace it with.
+    pub fn replace(&self, text: &str) -> Option<(Range<usize>, String)> {
+        let range = self.current()?;
+        let replacement = self.expand(text, range.clone()).ok()?;
+
+        Some((range, replacement))
+    }
+
+    /// Returns `text` with every match replaced, or `None` if nothing matched.
+    pub fn replace_all(&self, text: &str) -> Option<String> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        let mut replaced = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for range in &self.matches {
+            replaced.push_str(&text[last..range.start]);
+            replaced.push_str(&self.expand(text, range.clone()).ok()?);
+            last = range.end;
+        }
+
+        replaced.push_str(&text[last..]);
+
+        Some(replaced)
+    }
+
+    /// Splits matches into per-line ranges for the [`Highlighter`].
+    ///
+    /// [`Highlighter`]: crate::editor::highlighter::Highlighter
+    pub fn highlights(&self, text: &str) -> Vec<Match> {
+        if !self.is_open {
+            return vec![];
+        }
+
+        let lines = Lines::new(text);
+
+        self.matches
+            .iter()
+            .enumerate()
+            .flat_map(|(index, range)| {
+                let is_current = self.current == Some(index);
+
+                lines
+                    .split(range.clone())
+                    .map(move |(line, range)| Match {
+                        line,
+                        range,
+                        is_current,
+                    })
+            })
+            .collect()
+    }
+
+    fn pattern(&self) -> Result<Regex, String> {
+        let mut pattern = if self.regex {
+            self.query.clone()
+        } else {
+            escape(&self.query)
+        };
+
+        if self.whole_word {
+            pattern = format!(r"\b(?:{pattern})\b");
+        }
+
+        if !self.case_sensitive {
+            pattern = format!("(?i){pattern}");
+        }
+
+        match Regex::try_compile(&pattern) {
+            Some(error) => Err(error.to_string()),
+            None => Ok(Regex::new(pattern)),
+        }
+    }
+
+    /// Builds the replacement for the match at `range`, expanding `$n` capture groups in regex
+    /// mode.
+    fn expand(&self, text: &str, range: Range<usize>) -> Result<String, String> {
+        if !self.regex {
+            return Ok(self.replacement.clone());
+        }
+
+        let mut region = Region::new();
+        self.pattern()?
+            .search(text, range.start, text.len(), Some(&mut region));
+
+        let mut expanded = String::new();
+        let mut chars = self.replacement.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match (ch, chars.peek().copied()) {
+                ('$', Some('$')) => {
+                    chars.next();
+                    expanded.push('$');
+                }
+                ('$', Some(digit)) if digit.is_ascii_digit() => {
+                    chars.next();
+
+                    let group = digit.to_digit(10).unwrap_or_default() as usize;
+                    if let Some((start, end)) = region.pos(group) {
+                        expanded.push_str(&text[start..end]);
+                    }
+                }
+                _ => expanded.push(ch),
+            }
+        }
+
+        Ok(expanded)
+    }
+
+    fn status(&self) -> String {
+        if let Some(error) = &self.error {
+            return error.clone();
+        }
+
+        match (self.current, self.matches.len()) {
+            (_, 0) if self.query.is_empty() => String::new(),
+            (_, 0) => "No results".to_string(),
+            (Some(current), total) => format!("{} of {total}", current + 1),
+            (None, total) => format!("{total} results"),
+        }
+    }
+
+    pub fn view(&'_ self) -> Element<'_, Message> {
+        let query = text_input("Find", &self.query)
+            .id(QUERY_ID.clone())
+            .on_input(Message::SearchChanged)
+            .on_submit(Message::FindNext)
+            .padding(5)
+            .width(Length::Fill);
+
+        let replacement = text_input("Replace", &self.replacement)
+            .on_input(Message::ReplacementChanged)
+            .on_submit(Message::Replace)
+            .padding(5)
+            .width(Length::Fill);
+
+        let status = text(self.status()).size(14).class(if self.error.is_some() {
+            TextClass::Error
+        } else {
+            TextClass::Primary
+        });
+
+        let find_row = row![
+            query,
+            toggle_button(
+                icon(Icon::CaseSensitive),
+                "Match case",
+                self.case_sensitive,
+                Message::ToggleSearchOption(Toggle::CaseSensitive),
+            ),
+            toggle_button(
+                icon(Icon::WholeWord),
+                "Match whole word",
+                self.whole_word,
+                Message::ToggleSearchOption(Toggle::WholeWord),
+            ),
+            toggle_button(
+                icon(Icon::Regex),
+                "Use regular expression",
+                self.regex,
+                Message::ToggleSearchOption(Toggle::Regex),
+            ),
+            container(status).width(90),
+            control_button(
+                icon(Icon::ChevronUp),
+                "Previous match",
+                Message::FindPrevious
+            ),
+            control_button(icon(Icon::ChevronDown), "Next match", Message::FindNext),
+            control_button(icon(Icon::X), "Close", Message::CloseSearch),
+        ];
+
+        let replace_row = row![
+            replacement,
+            control_button(icon(Icon::Replace), "Replace", Message::Replace),
+            control_button(icon(Icon::ReplaceAll), "Replace all", Message::ReplaceAll),
+        ];
+
+        container(
+            column![
+                find_row.spacing(5).align_y(Alignment::Center),
+                replace_row.spacing(5).align_y(Alignment::Center),
+            ]
+            .spacing(5),
+        )
+        .padding([5, 10])
+        .width(Length::Fill)
+        .class(ContainerClass::Controls)
+        .into()
+    }
+}
+
+fn escape(query: &str) -> String {
+    query
+        .chars()
+        .fold(String::with_capacity(query.len()), |mut escaped, ch| {
+            if r"\.+*?()|[]{}^$#-".contains(ch) {
+                escaped.push('\\');
+            }
+
+            escaped.push(ch);
+            escaped
+        })
+}
@@ -0,0 +1,222 @@
+use crate::JETBRAINS_MONO;
+use crate::editor::highlighter::SyntaxTheme;
+use crate::editor::profile::Profile;
+use crate::editor::{Element, Message};
+use iced::widget::{button, checkbox, column, container, pick_list, row, text};
+use iced::{Alignment, Font, Length};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// How the editor looks and behaves, changed from the settings pane.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub indent_size: usize,
+    pub use_spaces: bool,
+    pub font: EditorFont,
+    pub font_size: f32,
+    /// Relative to the font size.
+    pub line_height: f32,
+    pub padding: f32,
+    pub syntax_theme: SyntaxTheme,
+    /// How long to wait after the last edit before validating, in milliseconds.
+    pub validation_delay: u64,
+    pub target_profile: Profile,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            indent_size: 4,
+            use_spaces: true,
+            font: EditorFont::default(),
+            font_size: 16.0,
+            line_height: 1.3,
+            padding: 10.0,
+            syntax_theme: SyntaxTheme::default(),
+            validation_delay: 300,
+            target_profile: Profile::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EditorFont {
+    #[default]
+    JetBrainsMono,
+    SystemMonospace,
+}
+
+impl EditorFont {
+    const ALL: [EditorFont; 2] = [EditorFont::JetBrainsMono, EditorFont::SystemMonospace];
+
+    pub fn font(&self) -> Font {
+        match self {
+            EditorFont::JetBrainsMono => JETBRAINS_MONO,
+            EditorFont::SystemMonospace => Font::MONOSPACE,
+        }
+    }
+}
+
+impl Display for EditorFont {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EditorFont::JetBrainsMono => "JetBrains Mono",
+            EditorFont::SystemMonospace => "System monospace",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Change {
+    IndentSize(usize),
+    UseSpaces(bool),
+    Font(EditorFont),
+    FontSize(f32),
+    LineHeight(f32),
+    Padding(f32),
+    SyntaxTheme(SyntaxTheme),
+    ValidationDelay(u64),
+    TargetProfile(Profile),
+}
+
+impl Settings {
+    /// Applies `change`, keeping values in a range the editor can still be used with.
+    pub fn apply(&mut self, change: Change) {
+        match change {
+            Change::IndentSize(size) => self.indent_size = size,
+            Change::UseSpaces(use_spaces) => self.use_spaces = use_spaces,
+            Change::Font(font) => self.font = font,
+            Change::FontSize(size) => self.font_size = size,
+            Change::LineHeight(height) => self.line_height = height,
+            Change::Padding(padding) => self.padding = padding,
+            Change::SyntaxTheme(theme) => self.syntax_theme = theme,
+            Change::ValidationDelay(delay) => self.validation_delay = delay,
+            Change::TargetProfile(profile) => self.target_profile = profile,
+        }
+
+        *self = std::mem::take(self).clamped();
+    }
+
+    /// Keeps values in a range the editor can still be used with, including ones edited into
+    /// the preferences file by hand.
+    pub fn clamped(self) -> Self {
+        Self {
+            indent_size: self.indent_size.clamp(1, 8),
+            font_size: self.font_size.clamp(8.0, 32.0),
+            // Stepping by tenths adds up float error, keep it tidy for the file
+            line_height: (self.line_height.clamp(1.0, 2.0) * 10.0).round() / 10.0,
+            padding: self.padding.clamp(0.0, 40.0),
+            validation_delay: self.validation_delay.min(5000),
+            ..self
+        }
+    }
+
+    pub fn indent(&self) -> String {
+        if self.use_spaces {
+            " ".repeat(self.indent_size)
+        } else {
+            "\t".to_string()
+        }
+    }
+
+    pub fn validation_delay(&self) -> Duration {
+        Duration::from_millis(self.validation_delay)
+    }
+
+    pub fn view(&'_ self) -> Element<'_, Message> {
+        column![
+            text("Settings").size(20),
+            setting(
+                "Indent size",
+                stepper(
+                    self.indent_size.to_string(),
+                    Message::ChangeSetting(Change::IndentSize(self.indent_size.saturating_sub(1))),
+                    Message::ChangeSetting(Change::IndentSize(self.indent_size + 1)),
+                ),
+            ),
+            setting(
+                "Indent with spaces",
+                checkbox("", self.use_spaces)
+                    .on_toggle(|use_spaces| Message::ChangeSetting(Change::UseSpaces(use_spaces))),
+            ),
+            setting(
+                "Font",
+                pick_list(EditorFont::ALL, Some(self.font), |font| {
+                    Message::ChangeSetting(Change::Font(font))
+                }),
+            ),
+            setting(
+                "Font size",
+                stepper(
+                    format!("{}", self.font_size),
+                    Message::ChangeSetting(Change::FontSize(self.font_size - 1.0)),
+                    Message::ChangeSetting(Change::FontSize(self.font_size + 1.0)),
+                ),
+            ),
+            setting(
+                "Line height",
+                stepper(
+                    format!("{:.1}", self.line_height),
+                    Message::ChangeSetting(Change::LineHeight(self.line_height - 0.1)),
+                    Message::ChangeSetting(Change::LineHeight(self.line_height + 0.1)),
+                ),
+            ),
+            setting(
+                "Padding",
+                stepper(
+                    format!("{}", self.padding),
+                    Message::ChangeSetting(Change::Padding(self.padding - 2.0)),
+                    Message::ChangeSetting(Change::Padding(self.padding + 2.0)),
+                ),
+            ),
+            setting(
+                "Syntax theme",
+                pick_list(SyntaxTheme::ALL, Some(self.syntax_theme), |theme| {
+                    Message::ChangeSetting(Change::SyntaxTheme(theme))
+                }),
+            ),
+            setting(
+                "Validation delay",
+                stepper(
+                    format!("{} ms", self.validation_delay),
+                    Message::ChangeSetting(Change::ValidationDelay(
+                        self.validation_delay.saturating_sub(100)
+                    )),
+                    Message::ChangeSetting(Change::ValidationDelay(self.validation_delay + 100)),
+                ),
+            ),
+            setting(
+                "Target profile",
+                pick_list(Profile::ALL, Some(self.target_profile), |profile| {
+                    Message::ChangeSetting(Change::TargetProfile(profile))
+                }),
+            ),
+        ]
+        .spacing(10)
+        .width(340)
+        .into()
+    }
+}
+
+pub fn setting<'a>(label: &'a str, control: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    row![
+        text(label).width(Length::Fill),
+        container(control).align_right(Length::Shrink),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+fn stepper<'a>(value: String, decrease: Message, increase: Message) -> Element<'a, Message> {
+    row![
+        button(text("-")).on_press(decrease),
+        container(text(value)).center_x(70),
+        button(text("+")).on_press(increase),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center)
+    .into()
+}
@@ -0,0 +1,96 @@
+//! Translates spans in the module naga validates, which is the prelude followed by the user's
+//! shader, back into the user's buffer.
+
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// Where a span from the validated module ended up.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Location {
+    /// In the user's shader. `range` is in bytes into their buffer, `line` and `column` are zero
+    /// based, `column` counting chars.
+    Shader {
+        range: Range<usize>,
+        line: usize,
+        column: usize,
+    },
+    /// Inside the prelude the user never sees, usually because their shader redefines or
+    /// misuses something it declares.
+    Prelude { line: usize, column: usize },
+}
+
+impl Location {
+    pub fn range(&self) -> Option<Range<usize>> {
+        match self {
+            Location::Shader { range, .. } => Some(range.clone()),
+            Location::Prelude { .. } => None,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::Shader { line, column, .. } => write!(f, "{}:{}", line + 1, column + 1),
+            Location::Prelude { line, column } => {
+                write!(f, "prelude {}:{}", line + 1, column + 1)
+            }
+        }
+    }
+}
+
+pub struct SourceMap<'a> {
+    prelude: String,
+    shader: &'a str,
+}
+
+impl<'a> SourceMap<'a> {
+    pub fn new(prelude: String, shader: &'a str) -> Self {
+        Self { prelude, shader }
+    }
+
+    /// The source naga is given for the shader.
+    pub fn module(&self) -> String {
+        format!("{}\n{}", self.prelude, self.shader)
+    }
+
+    /// Maps a byte range in [`Self::module`] to where it is in the user's shader.
+    pub fn locate(&self, range: Range<usize>) -> Location {
+        let prelude = self.prelude.as_str();
+        let offset = prelude.len() + 1;
+
+        if range.start < offset {
+            let (line, column) = line_column(prelude, range.start.min(prelude.len()));
+
+            return Location::Prelude { line, column };
+        }
+
+        let start = (range.start - offset).min(self.shader.len());
+        let end = (range.end - offset).clamp(start, self.shader.len());
+        let (line, column) = line_column(self.shader, start);
+
+        Location::Shader {
+            range: start..end,
+            line,
+            column,
+        }
+    }
+}
+
+fn line_column(text: &str, index: usize) -> (usize, usize) {
+    let before = &text[..floor_char_boundary(text, index)];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+    (
+        before.matches('\n').count(),
+        before[line_start..].chars().count(),
+    )
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+
+    index
+}
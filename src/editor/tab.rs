@@ -0,0 +1,206 @@
+use crate::Shader;
+use crate::editor::history::History;
+use crate::editor::options::Options;
+use crate::editor::{cursor, merge, params, validation};
+use iced::task;
+use iced::widget::text_editor::{self, Action, Edit};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Identifies a [`Tab`] across async tasks, since its index can change while they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Id(usize);
+
+impl Id {
+    fn unique() -> Self {
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A single open shader.
+pub struct Tab {
+    pub id: Id,
+    pub content: text_editor::Content,
+    pub history: History,
+    pub shader_path: Option<PathBuf>,
+    pub validation_status: validation::Status,
+    pub is_dirty: bool,
+    /// The buffer as it was last loaded or saved, to tell whether edits were undone.
+    saved: String,
+    /// Bumped on every edit so validation results for an older buffer can be told apart.
+    pub version: usize,
+    pub last_valid_shader: Option<Shader>,
+    pub options: Options,
+    /// The entry points found the last time the shader could be parsed.
+    pub entry_points: validation::EntryPoints,
+    /// The params the last valid shader declares.
+    pub params: Option<params::Layout>,
+    /// How far down the editor is scrolled, in pixels. iced doesn't expose the editor's scroll
+    /// position, so this follows the same rules as cosmic-text to keep track of it.
+    pub scroll: f32,
+    /// Validation that was scheduled but hasn't finished yet.
+    pub pending_validation: Option<task::Handle>,
+    /// Modification time of the file when we last looked at it.
+    pub disk_modified: Option<SystemTime>,
+}
+
+impl Tab {
+    pub fn new(shader_path: Option<PathBuf>, shader: &str) -> Self {
+        let content = text_editor::Content::with_text(shader);
+
+        Self {
+            id: Id::unique(),
+            saved: content.text(),
+            content,
+            history: History::default(),
+            shader_path,
+            validation_status: validation::Status::NeedsValidation,
+            is_dirty: false,
+            version: 0,
+            last_valid_shader: None,
+            options: Options::default(),
+            entry_points: validation::EntryPoints::default(),
+            params: None,
+            scroll: 0.0,
+            pending_validation: None,
+            disk_modified: None,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        self.shader_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map_or("Untitled".to_string(), |name| {
+                name.to_string_lossy().to_string()
+            })
+    }
+
+    pub fn is_valid(&self) -> bool {
+        matches!(self.validation_status, validation::Status::Validated)
+    }
+
+    /// Scrolls by `lines`, given `height` pixels of lines fit in the editor.
+    pub fn scroll_by(&mut self, lines: i32, line_height: f32, height: f32) {
+        self.scroll += lines as f32 * line_height;
+        self.keep_scroll_in_buffer(line_height, height);
+    }
+
+    /// Scrolls just enough to bring the cursor in view, given `height` pixels of lines fit in the
+    /// editor.
+    pub fn follow_cursor(&mut self, line_height: f32, height: f32) {
+        let (line, _) = self.content.cursor_position();
+        let top = line as f32 * line_height;
+
+        if top < self.scroll {
+            self.scroll = top;
+        } else if height > 0.0 && top + line_height > self.scroll + height {
+            self.scroll = top + line_height - height;
+        }
+
+        self.keep_scroll_in_buffer(line_height, height);
+    }
+
+    /// cosmic-text only pulls the view back up to the end of the buffer once the first line is
+    /// out of view.
+    fn keep_scroll_in_buffer(&mut self, line_height: f32, height: f32) {
+        let end = self.content.line_count() as f32 * line_height - height;
+
+        self.scroll = if self.scroll >= line_height {
+            self.scroll.min(end.max(0.0))
+        } else {
+            self.scroll.max(0.0)
+        };
+    }
+
+    /// Records that the buffer was edited.
+    pub fn touch(&mut self) {
+        self.is_dirty = self.content.text() != self.saved;
+        self.version += 1;
+    }
+
+    /// Replaces the whole buffer as a single undoable edit, keeping the cursor roughly in place.
+    pub fn replace(&mut self, text: String) {
+        let cursor = cursor::offset(&self.content, self.content.cursor_position());
+
+        self.history.checkpoint(&self.content, self.is_valid());
+        self.content.perform(Action::SelectAll);
+        self.content.perform(Action::Edit(Edit::Paste(Arc::new(text))));
+        cursor::move_to(&mut self.content, cursor);
+        self.touch();
+    }
+
+    /// Whether `contents` read from disk match what we last loaded or saved.
+    pub fn is_saved(&self, contents: &str) -> bool {
+        self.saved == normalize(contents)
+    }
+
+    /// Replaces the buffer with `contents` that changed on disk, discarding any edits.
+    pub fn reload(&mut self, contents: &str) {
+        self.replace(contents.to_string());
+        self.saved = self.content.text();
+        self.is_dirty = false;
+    }
+
+    /// Merges `contents` that changed on disk into the edited buffer, returning the number of
+    /// conflicts.
+    pub fn merge(&mut self, contents: &str) -> usize {
+        let merged = merge::merge(&self.saved, &self.content.text(), contents);
+
+        self.keep(contents);
+        self.replace(merged.text);
+
+        merged.conflicts
+    }
+
+    /// Keeps the edited buffer over `contents` that changed on disk. The buffer stays dirty
+    /// against the new contents.
+    pub fn keep(&mut self, contents: &str) {
+        self.saved = normalize(contents);
+        self.is_dirty = self.content.text() != self.saved;
+    }
+
+    /// Records that `contents` were written to `path`. The buffer stays dirty if it was edited
+    /// while saving.
+    pub fn saved(&mut self, path: PathBuf, contents: &str) {
+        // Saved somewhere new, start watching that file instead
+        if self.shader_path.as_ref() != Some(&path) {
+            self.disk_modified = None;
+        }
+
+        self.shader_path = Some(path);
+        self.saved = contents.to_string();
+        self.is_dirty = self.content.text() != self.saved;
+    }
+
+    /// The buffer as it is, without the line break [`text_editor::Content::text`] adds after a
+    /// last line that isn't empty, so searches can't match it.
+    pub fn text(&self) -> String {
+        let mut text = self.content.text();
+
+        if self
+            .content
+            .line(self.content.line_count() - 1)
+            .is_some_and(|line| !line.is_empty())
+        {
+            text.pop();
+        }
+
+        text
+    }
+}
+
+/// Matches [`text_editor::Content::text`], which always ends with a line break.
+fn normalize(contents: &str) -> String {
+    let mut contents = contents.to_string();
+
+    if !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+
+    contents
+}
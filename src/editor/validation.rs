@@ -1,10 +1,15 @@
+use crate::editor::diagnostics::{Diagnostic, Severity};
+use crate::editor::options::Options;
+use crate::editor::params;
+use crate::editor::profile::Target;
+use crate::editor::source_map::{Location, SourceMap};
 use crate::editor::{icon, Element, Message};
-use crate::FragmentShader;
+use crate::viewer::{Channel, prelude};
+use crate::{BUFFERS, FragmentShader, Shader, VertexStage};
 use crate::theme::ContainerClass;
+use iced::widget::shader::wgpu;
 use iced::widget::tooltip;
-use naga::valid::Capabilities;
 use naga::ShaderStage;
-use std::ops::Range;
 use std::sync::Arc;
 use lucide_icons::Icon;
 
@@ -41,43 +46,253 @@ impl Status {
     }
 }
 
+/// What validating a shader found.
+#[derive(Clone, Debug)]
+pub struct Validated {
+    /// Empty if the shader couldn't be parsed.
+    pub entry_points: EntryPoints,
+    /// The params the shader declares, if it's valid and has any.
+    pub params: Option<params::Layout>,
+    pub result: Result<Shader, Error>,
+}
+
+/// The names of a module's entry points by stage.
+#[derive(Clone, Debug, Default)]
+pub struct EntryPoints {
+    pub fragment: Vec<String>,
+    pub vertex: Vec<String>,
+}
+
+impl EntryPoints {
+    pub fn is_empty(&self) -> bool {
+        self.fragment.is_empty() && self.vertex.is_empty()
+    }
+}
+
+/// Validates `shader` with the entry points picked in `options`, or the default ones for those
+/// that weren't.
+pub async fn validate(
+    shader: Arc<FragmentShader>,
+    target: Target,
+    options: Options,
+) -> Validated {
+    // Naga can take a while on big shaders, don't hold up the executor while it works
+    tokio::task::spawn_blocking(move || {
+        let mut entry_points = EntryPoints::default();
+        let result = check(shader, target, options, &mut entry_points);
+
+        let (result, params) = match result {
+            Ok((shader, params)) => (Ok(shader), params),
+            Err(error) => (Err(error), None),
+        };
+
+        Validated {
+            entry_points,
+            params,
+            result,
+        }
+    })
+    .await
+    .unwrap_or_else(|error| Validated {
+        entry_points: EntryPoints::default(),
+        params: None,
+        result: Err(Error::validation(error.to_string())),
+    })
+}
+
 // Assumes shader is WGSL
-pub async fn validate(shader: Arc<FragmentShader>) -> Result<Arc<FragmentShader>, Error> {
+fn check(
+    shader: Arc<FragmentShader>,
+    target: Target,
+    options: Options,
+    entry_points: &mut EntryPoints,
+) -> Result<(Shader, Option<params::Layout>), Error> {
     // Parse separately so we can show errors instead of panicking on pipeline creation
-    let concat_shader = format!(
-        "{}\n{}",
-        include_str!("../viewer/shaders/uniforms.wgsl"),
-        shader
+    let source_map = SourceMap::new(
+        prelude(options.has_buffers(), options.channels_used()),
+        &shader,
     );
 
     let parsed =
-        naga::front::wgsl::parse_str(&concat_shader).map_err(|parse_error| Error::Parse {
+        naga::front::wgsl::parse_str(&source_map.module()).map_err(|parse_error| Error::Parse {
             message: parse_error.message().to_string(),
             errors: parse_error
                 .labels()
-                .filter_map(|(span, err)| span.to_range().map(|r| (r, err.to_string())))
+                .filter_map(|(span, err)| {
+                    span.to_range()
+                        .map(|range| (source_map.locate(range), err.to_string()))
+                })
                 .collect::<Vec<_>>(),
         })?;
 
-    let contains_frag = parsed
-        .entry_points
-        .iter()
-        .any(|ep| ep.name == "fs_main" && ep.stage == ShaderStage::Fragment);
+    let names = |stage| {
+        parsed
+            .entry_points
+            .iter()
+            .filter(|ep| ep.stage == stage)
+            .map(|ep| ep.name.clone())
+            .collect()
+    };
 
-    if !contains_frag {
-        return Err(Error::Validation(
-            "Missing fragment entry point 'fs_main'.".to_string(),
-        ));
+    entry_points.fragment = names(ShaderStage::Fragment);
+    entry_points.vertex = names(ShaderStage::Vertex);
+
+    let entry_point = pick(
+        options.entry_point,
+        &entry_points.fragment,
+        Shader::DEFAULT_ENTRY_POINT,
+        "fragment",
+    )?;
+
+    let vertex = match options.vertex {
+        Some(vertex) => Some(VertexStage {
+            entry_point: pick(
+                vertex.entry_point,
+                &entry_points.vertex,
+                Shader::DEFAULT_VERTEX_ENTRY_POINT,
+                "vertex",
+            )?,
+            vertex_count: vertex.vertex_count,
+            instance_count: vertex.instance_count,
+        }),
+        None => None,
+    };
+
+    let mut buffers: [Option<String>; BUFFERS] = Default::default();
+
+    for (buffer, picked) in buffers.iter_mut().zip(options.buffers) {
+        if picked.is_some() {
+            *buffer = Some(pick(
+                picked,
+                &entry_points.fragment,
+                Shader::DEFAULT_ENTRY_POINT,
+                "fragment",
+            )?);
+        }
     }
 
+    // The editor fills in images as they load, validation only needs to know what's declared
+    let channels = options.channels.map(|picked| {
+        picked.map(|picked| Channel {
+            image: None,
+            filter: picked.filter,
+            wrap: picked.wrap,
+        })
+    });
+
     naga::valid::Validator::new(
         naga::valid::ValidationFlags::default(),
-        Capabilities::all(), // TODO: Get from device capabilities
+        target.capabilities,
     )
     .validate(&parsed)
-    .map_err(|err| Error::Validation(err.to_string()))?;
+    .map_err(|error| {
+        let mut causes = Vec::new();
+        let mut source = std::error::Error::source(&error);
+
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+
+        Error::Validation {
+            message: error.as_inner().to_string(),
+            causes,
+            errors: error
+                .spans()
+                .filter_map(|(span, label)| {
+                    span.to_range()
+                        .map(|range| (source_map.locate(range), label.clone()))
+                })
+                .collect(),
+        }
+    })?;
 
-    Ok(shader)
+    let params = params::reflect(&parsed, &shader);
+
+    if let Some(limits) = &target.limits {
+        check_limits(&parsed, &source_map, params.as_ref(), limits)?;
+    }
+
+    let shader = Shader {
+        source: shader,
+        entry_point,
+        vertex,
+        buffers,
+        channels,
+        params_size: params.as_ref().map(|params| params.size),
+    };
+
+    Ok((shader, params))
+}
+
+/// naga doesn't know about the device, catch what it can't create before the viewer tries to.
+fn check_limits(
+    module: &naga::Module,
+    source_map: &SourceMap,
+    params: Option<&params::Layout>,
+    limits: &wgpu::Limits,
+) -> Result<(), Error> {
+    let out_of_range: Vec<_> = module
+        .global_variables
+        .iter()
+        .filter(|(_, global)| {
+            global
+                .binding
+                .as_ref()
+                .is_some_and(|binding| binding.group >= limits.max_bind_groups)
+        })
+        .filter_map(|(handle, _)| {
+            module.global_variables.get_span(handle).to_range().map(|range| {
+                (
+                    source_map.locate(range),
+                    format!("Only {} groups are available", limits.max_bind_groups),
+                )
+            })
+        })
+        .collect();
+
+    if !out_of_range.is_empty() {
+        return Err(Error::Validation {
+            message: format!(
+                "Bind groups from {} on aren't available on the target.",
+                limits.max_bind_groups
+            ),
+            causes: Vec::new(),
+            errors: out_of_range,
+        });
+    }
+
+    match params {
+        Some(params) if params.size > u64::from(limits.max_uniform_buffer_binding_size) => {
+            Err(Error::validation(format!(
+                "The params uniform takes {} bytes, more than the {} the target allows.",
+                params.size, limits.max_uniform_buffer_binding_size
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The entry point the user picked out of `found`, or without a choice the usual `default` name
+/// and otherwise whichever there is.
+fn pick(
+    picked: Option<String>,
+    found: &[String],
+    default: &str,
+    stage: &str,
+) -> Result<String, Error> {
+    match picked {
+        Some(name) if found.contains(&name) => Ok(name),
+        Some(name) => Err(Error::validation(format!(
+            "Missing {stage} entry point '{name}'."
+        ))),
+        None => found
+            .iter()
+            .find(|name| *name == default)
+            .or(found.first())
+            .cloned()
+            .ok_or_else(|| Error::validation(format!("Missing a {stage} entry point."))),
+    }
 }
 
 #[derive(thiserror::Error, Clone, Debug)]
@@ -85,8 +300,100 @@ pub enum Error {
     #[error("Shader parsing error")]
     Parse {
         message: String,
-        errors: Vec<(Range<usize>, String)>,
+        errors: Vec<(Location, String)>,
+    },
+    #[error("Validation error: {message}")]
+    Validation {
+        message: String,
+        /// The errors that led to `message`, outermost first.
+        causes: Vec<String>,
+        errors: Vec<(Location, String)>,
     },
-    #[error("Validation error: {0}")]
-    Validation(String),
+    /// naga was fine with the shader but wgpu couldn't build a pipeline from it.
+    #[error("Pipeline error: {message}")]
+    Pipeline {
+        message: String,
+        causes: Vec<String>,
+    },
+}
+
+impl Error {
+    fn validation(message: String) -> Self {
+        Error::Validation {
+            message,
+            causes: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// wgpu formats the whole chain into one description, split it up again.
+    pub fn pipeline(description: &str) -> Self {
+        let mut lines = description
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && *line != "Caused by:");
+
+        Error::Pipeline {
+            message: format!(
+                "Couldn't create the render pipeline: {}",
+                lines.next().unwrap_or("unknown error")
+            ),
+            causes: lines.map(str::to_string).collect(),
+        }
+    }
+
+    /// Every problem this error is made of, most important first.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let (message, causes, errors) = match self {
+            Error::Parse { message, errors } => (message, &[][..], &errors[..]),
+            Error::Validation {
+                message,
+                causes,
+                errors,
+            } => (message, &causes[..], &errors[..]),
+            Error::Pipeline { message, causes } => (message, &causes[..], &[][..]),
+        };
+
+        let causes = causes.iter().map(|cause| Diagnostic {
+            severity: Severity::Note,
+            message: format!("caused by: {cause}"),
+            label: String::new(),
+            location: None,
+        });
+
+        if errors.is_empty() {
+            let error = Diagnostic {
+                severity: Severity::Error,
+                message: message.clone(),
+                label: String::new(),
+                location: None,
+            };
+
+            return std::iter::once(error).chain(causes).collect();
+        }
+
+        errors
+            .iter()
+            .enumerate()
+            .map(|(i, (location, label))| {
+                // naga puts the span that caused the error first, the rest add context
+                if i == 0 {
+                    Diagnostic {
+                        severity: Severity::Error,
+                        message: message.clone(),
+                        label: label.clone(),
+                        location: Some(location.clone()),
+                    }
+                } else {
+                    Diagnostic {
+                        severity: Severity::Note,
+                        message: label.clone(),
+                        label: String::new(),
+                        location: Some(location.clone()),
+                    }
+                }
+            })
+            .chain(causes)
+            .collect()
+    }
 }
@@ -1,21 +1,76 @@
 mod editor;
 mod preferences;
 mod theme;
+mod toast;
 mod viewer;
 
 use crate::editor::{Editor, Event};
-use crate::preferences::Preferences;
 use crate::theme::Theme;
+use crate::toast::Toasts;
 use crate::viewer::Viewer;
 use iced::font::{Family, Stretch, Style, Weight};
 use iced::widget::pane_grid::Configuration;
-use iced::widget::{container, pane_grid};
-use iced::{Element, Font, Length, Size, Task};
+use iced::widget::{container, pane_grid, stack};
+use iced::{Element, Font, Length, Size, Subscription, Task, window};
 use lucide_icons::LUCIDE_FONT_BYTES;
 use std::sync::Arc;
 
 pub type FragmentShader = String;
 
+/// How many buffers a multi-pass shader can render to, Buffer A to D.
+pub const BUFFERS: usize = 4;
+
+/// How many textures can be bound for a shader to sample, `channel0` to `channel3`.
+pub const CHANNELS: usize = 4;
+
+/// A shader that passed validation, along with how to run it.
+#[derive(Clone, Debug)]
+pub struct Shader {
+    pub source: Arc<FragmentShader>,
+    pub entry_point: String,
+    /// The shader's own vertex stage, the full-screen quad is drawn without one.
+    pub vertex: Option<VertexStage>,
+    /// The fragment entry point rendering to each buffer, for those that are used.
+    pub buffers: [Option<String>; BUFFERS],
+    pub channels: [Option<viewer::Channel>; CHANNELS],
+    /// The size of the shader's params uniform, when it declares one.
+    pub params_size: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct VertexStage {
+    pub entry_point: String,
+    pub vertex_count: u32,
+    pub instance_count: u32,
+}
+
+impl Shader {
+    /// The entry point used unless the shader or the user picks another.
+    pub const DEFAULT_ENTRY_POINT: &str = "fs_main";
+    /// Like [`Self::DEFAULT_ENTRY_POINT`], for a custom vertex stage.
+    pub const DEFAULT_VERTEX_ENTRY_POINT: &str = "vs_main";
+
+    /// One of the shaders bundled with Halo, which all use the default entry point.
+    pub fn bundled(source: &str) -> Self {
+        Self {
+            source: Arc::new(source.to_string()),
+            entry_point: Self::DEFAULT_ENTRY_POINT.to_string(),
+            vertex: None,
+            buffers: Default::default(),
+            channels: Default::default(),
+            params_size: None,
+        }
+    }
+
+    pub fn has_buffers(&self) -> bool {
+        self.buffers.iter().any(Option::is_some)
+    }
+
+    pub fn channels_used(&self) -> [bool; CHANNELS] {
+        self.channels.each_ref().map(Option::is_some)
+    }
+}
+
 const HALO: &str = "Halo";
 
 const JETBRAINS_MONO: Font = Font {
@@ -28,6 +83,8 @@ const JETBRAINS_MONO: Font = Font {
 fn main() -> iced::Result {
     iced::application(Halo::title, Halo::update, Halo::view)
         .theme(Halo::theme)
+        .subscription(Halo::subscription)
+        .exit_on_close_request(false)
         .font(include_bytes!("../fonts/JetBrainsMono-Regular.ttf").as_slice())
         .font(LUCIDE_FONT_BYTES)
         .default_font(Font::MONOSPACE)
@@ -39,6 +96,7 @@ struct Halo {
     viewer: Viewer,
     editor: Editor,
     panes: pane_grid::State<Pane>,
+    toasts: Toasts,
 }
 
 // TODO: Toggle editor
@@ -46,7 +104,14 @@ struct Halo {
 enum Message {
     PaneResized(pane_grid::ResizeEvent),
     Editor(editor::Message),
-    Loaded(Result<(Preferences, Arc<FragmentShader>), preferences::Error>),
+    Toast(toast::Message),
+    Loaded(Result<preferences::Session, preferences::Error>),
+    DeviceReported(viewer::Device),
+    PipelineFailed(viewer::PipelineError),
+    Playback(viewer::PlaybackChange),
+    /// Sent every frame while playing, so the playback controls keep up with the animation.
+    Frame,
+    CloseRequested,
 }
 
 impl Halo {
@@ -62,6 +127,7 @@ impl Halo {
                     a: Box::new(Configuration::Pane(Pane::Viewer)),
                     b: Box::new(Configuration::Pane(Pane::Editor)),
                 }),
+                toasts: Toasts::default(),
             },
             // TODO: Load last shader file from settings
             Task::perform(preferences::load(), Message::Loaded),
@@ -69,7 +135,7 @@ impl Halo {
     }
 
     fn title(&self) -> String {
-        HALO.to_string()
+        format!("{} - {HALO}", self.editor.title())
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
@@ -78,21 +144,45 @@ impl Halo {
                 let (event, cmd) = self.editor.update(msg);
 
                 match event {
-                    Event::UpdatePipeline(shader) => {
+                    Event::UpdatePipeline(shader, params) => {
                         self.viewer.last_valid_shader = shader;
+                        self.viewer.params = params;
                         self.viewer.version += 1;
                     }
+                    Event::UpdateParams(params) => self.viewer.params = params,
+                    Event::ResetBuffers => self.viewer.generation += 1,
+                    Event::Notify(toast) => self.toasts.push(toast),
+                    Event::Exit => return iced::exit(),
                     _ => {}
                 };
 
                 return cmd.map(Message::Editor);
             }
+            Message::Toast(toast::Message::Dismiss(id)) => {
+                self.toasts.dismiss(id);
+            }
+            Message::Toast(toast::Message::Retry(id)) => {
+                if let Some(retry) = self.toasts.dismiss(id).and_then(|toast| toast.retry) {
+                    return self.update(Message::Editor(retry));
+                }
+            }
             Message::PaneResized(pane_grid::ResizeEvent { split, ratio }) => {
                 self.panes.resize(split, ratio);
             }
             Message::Loaded(result) => {
                 return self.update(Message::Editor(editor::Message::Init(result)));
             }
+            Message::DeviceReported(device) => {
+                return self.update(Message::Editor(editor::Message::DeviceReported(device)));
+            }
+            Message::PipelineFailed(error) => {
+                return self.update(Message::Editor(editor::Message::PipelineFailed(error)));
+            }
+            Message::Playback(change) => self.viewer.update_playback(change),
+            Message::Frame => {}
+            Message::CloseRequested => {
+                return self.update(Message::Editor(editor::Message::Exit));
+            }
         }
 
         Task::none()
@@ -104,12 +194,31 @@ impl Halo {
         })
         .on_resize(10, Message::PaneResized);
 
-        container(panes)
-            .width(Length::Fill)
-            .height(Length::Fill)
+        let content = container(panes).width(Length::Fill).height(Length::Fill);
+
+        let toasts = self.toasts.view().map(|toasts| toasts.map(Message::Toast));
+        let prompt = self.editor.prompt().map(|prompt| prompt.map(Message::Editor));
+
+        stack![content]
+            .push_maybe(toasts)
+            .push_maybe(prompt)
             .into()
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        let frames = if self.viewer.playback.is_playing() {
+            window::frames().map(|_| Message::Frame)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([
+            window::close_requests().map(|_| Message::CloseRequested),
+            self.editor.subscription().map(Message::Editor),
+            frames,
+        ])
+    }
+
     fn theme(&self) -> Theme {
         Theme::Dark
     }
@@ -1,52 +1,335 @@
 use crate::FragmentShader;
+use crate::editor::{Options, Settings};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
+use std::io;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-const PATH: &'static str = concat!(env!("CARGO_MANIFEST_DIR"), "/preferences.json");
+/// Where preferences were kept before they moved to the config directory.
+const LEGACY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/preferences.json");
+const FILE_NAME: &str = "preferences.json";
+const PATH_VAR: &str = "HALO_PREFERENCES";
+const PATH_FLAG: &str = "--preferences";
+
+/// The preferences file, from `--preferences <path>`, `HALO_PREFERENCES` or the platform's
+/// config directory, in that order.
+pub static PATH: Lazy<PathBuf> = Lazy::new(|| {
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == PATH_FLAG {
+            if let Some(path) = args.next() {
+                return PathBuf::from(path);
+            }
+        } else if let Some(path) = arg
+            .strip_prefix(PATH_FLAG)
+            .and_then(|arg| arg.strip_prefix('='))
+        {
+            return PathBuf::from(path);
+        }
+    }
+
+    if let Some(path) = std::env::var_os(PATH_VAR).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    dirs::config_dir()
+        .map(|dir| dir.join("halo"))
+        .unwrap_or_default()
+        .join(FILE_NAME)
+});
+
+/// The schema version written by this build. Bump it along with a new entry in [`MIGRATIONS`]
+/// whenever a change can't be covered by a default.
+pub const VERSION: u32 = 2;
+
+/// Upgrades preferences one version at a time, indexed by the version they upgrade from.
+const MIGRATIONS: [fn(&mut Map<String, Value>); VERSION as usize] = [from_v0, from_v1];
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Preferences {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_shader_path: Option<PathBuf>,
+    /// Files written before versioning have none, so this defaults to 0 rather than [`VERSION`].
+    #[serde(default)]
+    pub version: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub open_tabs: Vec<PathBuf>,
+    pub active_tab: usize,
+    /// Most recently opened first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recent_files: Vec<PathBuf>,
     pub auto_validate: bool,
+    pub editor: Settings,
+    /// How each file's shader is run, for files where that was changed.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<PathBuf, Options>,
 }
 
-pub async fn load() -> Result<(Preferences, Arc<FragmentShader>), Error> {
-    let file = tokio::fs::read_to_string(PATH)
-        .await
-        .map_err(|_| Error::Io)?;
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            open_tabs: Vec::new(),
+            active_tab: 0,
+            recent_files: Vec::new(),
+            auto_validate: true,
+            editor: Settings::default(),
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+/// Preferences along with the contents of the tabs they restore.
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub preferences: Preferences,
+    pub shaders: Vec<(PathBuf, Arc<FragmentShader>)>,
+    /// Problems that were recovered from by falling back to defaults.
+    pub warnings: Vec<String>,
+    /// Set when the preferences were copied over from where older versions kept them.
+    pub imported_from: Option<PathBuf>,
+}
+
+pub async fn load() -> Result<Session, Error> {
+    let mut imported_from = None;
+
+    let file = match tokio::fs::read_to_string(&*PATH).await {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            let file = import_legacy().await?;
+            imported_from = Some(PathBuf::from(LEGACY_PATH));
+            file
+        }
+        file => file.map_err(|error| Error::Io(error.kind()))?,
+    };
+
+    let mut warnings = Vec::new();
+    let preferences = parse(&file, &mut warnings);
+
+    let mut shaders = Vec::with_capacity(preferences.open_tabs.len());
 
-    let prefs: Preferences = serde_json::from_str(&file).map_err(|_| Error::Deserialize)?;
+    // A tab whose file went missing shouldn't stop the others from being restored
+    for shader_path in &preferences.open_tabs {
+        match tokio::fs::read_to_string(shader_path).await {
+            Ok(shader) => shaders.push((shader_path.clone(), Arc::new(shader))),
+            Err(e) => warnings.push(format!(
+                "Couldn't reopen {}: {}",
+                shader_path.display(),
+                e.kind()
+            )),
+        }
+    }
+
+    Ok(Session {
+        preferences,
+        shaders,
+        warnings,
+        imported_from,
+    })
+}
+
+/// Reads as much of `file` as possible, resetting anything unreadable to its default.
+fn parse(file: &str, warnings: &mut Vec<String>) -> Preferences {
+    let mut prefs = match serde_json::from_str(file) {
+        Ok(Value::Object(prefs)) => prefs,
+        Ok(_) => {
+            warnings.push(format!("{} isn't a JSON object", PATH.display()));
+            return Preferences::default();
+        }
+        Err(error) => {
+            warnings.push(format!("{} isn't valid JSON: {error}", PATH.display()));
+            return Preferences::default();
+        }
+    };
+
+    let version = prefs.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+    if version > VERSION as u64 {
+        warnings.push(format!(
+            "{} was written by a newer version of Halo, some settings may be lost",
+            PATH.display()
+        ));
+    }
+
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(&mut prefs);
+    }
+
+    prefs.insert("version".to_string(), VERSION.into());
 
-    let shader = if let Some(shader_path) = &prefs.last_shader_path {
-        tokio::fs::read_to_string(shader_path).await.map_err(|e| {
-            println!("Error reading shader at path: {shader_path:?} -- {e:?}");
-            Error::Io
-        })?
-    } else {
-        include_str!("viewer/shaders/default_frag.wgsl").to_string()
+    // Take each setting on its own so a bad one doesn't throw away the rest
+    let Ok(Value::Object(mut recovered)) = serde_json::to_value(Preferences::default()) else {
+        return Preferences::default();
     };
 
-    Ok((prefs, Arc::new(shader)))
+    for (key, value) in prefs {
+        let mut candidate = recovered.clone();
+        candidate.insert(key.clone(), value);
+
+        if serde_json::from_value::<Preferences>(Value::Object(candidate.clone())).is_ok() {
+            recovered = candidate;
+        } else {
+            warnings.push(format!(
+                "`{key}` in {} was invalid and reset",
+                PATH.display()
+            ));
+        }
+    }
+
+    let mut prefs: Preferences =
+        serde_json::from_value(Value::Object(recovered)).unwrap_or_default();
+    prefs.editor = prefs.editor.clamped();
+
+    prefs
+}
+
+/// Version 0 remembered a single shader instead of a list of tabs.
+fn from_v0(prefs: &mut Map<String, Value>) {
+    if let Some(path) = prefs.remove("last_shader_path")
+        && !path.is_null()
+        && !prefs.contains_key("open_tabs")
+    {
+        prefs.insert("open_tabs".to_string(), Value::Array(vec![path]));
+    }
+}
+
+/// Version 1 only remembered each file's fragment entry point.
+fn from_v1(prefs: &mut Map<String, Value>) {
+    if let Some(Value::Object(entry_points)) = prefs.remove("entry_points") {
+        let files = entry_points
+            .into_iter()
+            .map(|(path, entry_point)| {
+                let options = Map::from_iter([("entry_point".to_string(), entry_point)]);
+
+                (path, Value::Object(options))
+            })
+            .collect();
+
+        prefs.insert("files".to_string(), Value::Object(files));
+    }
 }
 
 pub async fn save(preferences: Preferences) -> Result<(), Error> {
-    let pref = serde_json::to_string(&preferences).map_err(|_| Error::Serialize)?;
+    let pref =
+        serde_json::to_string(&preferences).map_err(|error| Error::Serialize(error.to_string()))?;
+
+    write(pref).await.map_err(|error| Error::Io(error.kind()))
+}
+
+/// Writes to a temporary file first so a crash mid-write can't leave half a file behind. Each
+/// write gets its own, saves can overlap and shouldn't move each other's half-written files.
+async fn write(contents: String) -> io::Result<()> {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    if let Some(dir) = PATH.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    let temp = PATH.with_extension(format!(
+        "json.{}-{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
 
-    tokio::fs::write(&PATH, pref).await.map_err(|_| Error::Io)
+    tokio::fs::write(&temp, contents).await?;
+    tokio::fs::rename(&temp, &*PATH).await
+}
+
+/// Copies preferences left in the source tree by older versions, which stay where they are for
+/// those versions to keep using.
+async fn import_legacy() -> Result<String, Error> {
+    let file = tokio::fs::read_to_string(LEGACY_PATH)
+        .await
+        .map_err(|error| Error::Io(error.kind()))?;
+
+    write(file.clone())
+        .await
+        .map_err(|error| Error::Io(error.kind()))?;
+
+    Ok(file)
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum Error {
-    Io,
-    Deserialize,
-    Serialize,
+    Io(io::ErrorKind),
+    Serialize(String),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Error::Io(kind) => write!(f, "{}: {kind}", PATH.display()),
+            Error::Serialize(error) => write!(f, "Couldn't serialize preferences: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v0_last_shader_becomes_open_tabs() {
+        let mut warnings = Vec::new();
+        let prefs = parse(r#"{"last_shader_path": "a.wgsl"}"#, &mut warnings);
+
+        assert_eq!(prefs.open_tabs, [PathBuf::from("a.wgsl")]);
+        assert_eq!(prefs.version, VERSION);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn v1_entry_points_become_files() {
+        let mut warnings = Vec::new();
+        let prefs = parse(
+            r#"{"version": 1, "entry_points": {"a.wgsl": "main"}}"#,
+            &mut warnings,
+        );
+
+        let options = &prefs.files[&PathBuf::from("a.wgsl")];
+
+        assert_eq!(options.entry_point.as_deref(), Some("main"));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn bad_key_resets_only_itself() {
+        let mut warnings = Vec::new();
+        let prefs = parse(
+            r#"{"version": 2, "auto_validate": false, "active_tab": "first"}"#,
+            &mut warnings,
+        );
+
+        assert!(!prefs.auto_validate);
+        assert_eq!(prefs.active_tab, 0);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("active_tab"));
+    }
+
+    #[test]
+    fn invalid_json_falls_back_to_defaults() {
+        let mut warnings = Vec::new();
+        let prefs = parse("{ not json", &mut warnings);
+
+        assert!(prefs.auto_validate);
+        assert!(prefs.open_tabs.is_empty());
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn newer_version_warns() {
+        let mut warnings = Vec::new();
+        let prefs = parse(
+            &format!(r#"{{"version": {}, "auto_validate": false}}"#, VERSION + 1),
+            &mut warnings,
+        );
+
+        assert!(!prefs.auto_validate);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("newer version"));
     }
 }
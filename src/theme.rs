@@ -2,7 +2,8 @@ use iced::application::Appearance;
 use iced::widget::button::Status;
 use iced::widget::pane_grid::Highlight;
 use iced::widget::scrollable::Rail;
-use iced::widget::{button, checkbox, container, pane_grid, scrollable, text, text_editor};
+use iced::overlay::menu;
+use iced::widget::{button, checkbox, container, pane_grid, pick_list, scrollable, slider, text, text_editor, text_input};
 use iced::{application, Border, Color};
 use std::default::Default;
 
@@ -67,6 +68,28 @@ impl Theme {
             Theme::Dark => Palette::default(),
         }
     }
+
+    /// Text color for search matches in the editor
+    pub fn search_match(&self, is_current: bool) -> Color {
+        let palette = self.palette();
+
+        if is_current {
+            palette.accent
+        } else {
+            palette.accent_secondary
+        }
+    }
+
+    /// Text color for spans marked by a diagnostic in the editor
+    pub fn diagnostic(&self, is_error: bool) -> Color {
+        let palette = self.palette();
+
+        if is_error {
+            palette.error
+        } else {
+            palette.base_lightest
+        }
+    }
 }
 
 impl application::DefaultStyle for Theme {
@@ -85,6 +108,12 @@ pub enum ContainerClass {
     Tooltip,
     Controls,
     Error,
+    Dialog,
+    Backdrop,
+    /// Shows a color picked for a shader parameter.
+    Swatch(Color),
+    /// The area a two-dimensional parameter is dragged around in.
+    Pad,
     #[default]
     None,
 }
@@ -123,6 +152,34 @@ impl container::Catalog for Theme {
                     color: palette.error,
                 };
             },
+            ContainerClass::Dialog => {
+                style.background = Some(palette.base_darkest.into());
+                style.text_color = Some(palette.text);
+                style.border = Border {
+                    radius: BORDER_RADIUS.into(),
+                    width: BORDER_WIDTH,
+                    color: palette.base_darker,
+                };
+            },
+            ContainerClass::Backdrop => {
+                style.background = Some(palette.background.scale_alpha(0.7).into());
+            },
+            ContainerClass::Swatch(color) => {
+                style.background = Some((*color).into());
+                style.border = Border {
+                    radius: BORDER_RADIUS.into(),
+                    width: 1.0,
+                    color: palette.base_lighter,
+                };
+            },
+            ContainerClass::Pad => {
+                style.background = Some(palette.base_darkest.into());
+                style.border = Border {
+                    radius: BORDER_RADIUS.into(),
+                    width: 1.0,
+                    color: palette.base,
+                };
+            },
             _ => {}
         };
 
@@ -135,6 +192,8 @@ pub enum TextClass {
     #[default]
     Primary,
     Error,
+    Warning,
+    Disabled,
 }
 
 impl text::Catalog for Theme {
@@ -154,6 +213,12 @@ impl text::Catalog for Theme {
             TextClass::Error => text::Style {
                 color: Some(palette.error),
             },
+            TextClass::Warning => text::Style {
+                color: Some(palette.accent_secondary),
+            },
+            TextClass::Disabled => text::Style {
+                color: Some(palette.disabled),
+            },
         }
     }
 }
@@ -193,6 +258,10 @@ impl pane_grid::Catalog for Theme {
 pub enum ButtonClass {
     #[default]
     Control,
+    Toggled,
+    Tab,
+    ActiveTab,
+    MenuItem,
 }
 
 impl button::Catalog for Theme {
@@ -202,7 +271,7 @@ impl button::Catalog for Theme {
         ButtonClass::Control
     }
 
-    fn style(&self, _class: &Self::Class<'_>, status: Status) -> button::Style {
+    fn style(&self, class: &Self::Class<'_>, status: Status) -> button::Style {
         let mut style = button::Style::default();
         let palette = self.palette();
 
@@ -221,6 +290,35 @@ impl button::Catalog for Theme {
             _ => {}
         };
 
+        match class {
+            ButtonClass::Control => {},
+            ButtonClass::Toggled => {
+                style.border.width = 1.0;
+                style.border.color = palette.accent_secondary;
+            },
+            ButtonClass::Tab => {
+                style.background = match status {
+                    Status::Hovered | Status::Pressed => Some(palette.base.into()),
+                    _ => None,
+                };
+                style.text_color = palette.disabled;
+            },
+            ButtonClass::MenuItem => {
+                style.background = match status {
+                    Status::Hovered | Status::Pressed => Some(palette.base_darker.into()),
+                    _ => None,
+                };
+            },
+            ButtonClass::ActiveTab => {
+                style.background = Some(palette.background.into());
+                style.border = Border {
+                    radius: Default::default(),
+                    width: 0.0,
+                    color: Default::default(),
+                };
+            },
+        };
+
         style
     }
 }
@@ -264,6 +362,39 @@ impl checkbox::Catalog for Theme {
     }
 }
 
+impl slider::Catalog for Theme {
+    type Class<'a> = ();
+
+    fn default<'a>() -> Self::Class<'a> {}
+
+    fn style(&self, _class: &Self::Class<'_>, status: slider::Status) -> slider::Style {
+        let palette = self.palette();
+
+        let handle = match status {
+            slider::Status::Active => palette.base_lighter,
+            slider::Status::Hovered | slider::Status::Dragged => palette.base_lightest,
+        };
+
+        slider::Style {
+            rail: slider::Rail {
+                backgrounds: (palette.accent.into(), palette.base.into()),
+                width: 4.0,
+                border: Border {
+                    radius: 2.0.into(),
+                    width: 0.0,
+                    color: Default::default(),
+                },
+            },
+            handle: slider::Handle {
+                shape: slider::HandleShape::Circle { radius: 7.0 },
+                background: handle.into(),
+                border_width: 0.0,
+                border_color: Default::default(),
+            },
+        }
+    }
+}
+
 impl text_editor::Catalog for Theme {
     type Class<'a> = ();
 
@@ -323,3 +454,100 @@ impl scrollable::Catalog for Theme {
         }
     }
 }
+
+impl text_input::Catalog for Theme {
+    type Class<'a> = ();
+
+    fn default<'a>() -> Self::Class<'a> {}
+
+    fn style(&self, _class: &Self::Class<'_>, status: text_input::Status) -> text_input::Style {
+        let palette = self.palette();
+
+        let style = text_input::Style {
+            background: palette.background.into(),
+            border: Border {
+                radius: 2.0.into(),
+                width: 1.0,
+                color: palette.base,
+            },
+            icon: palette.text,
+            placeholder: palette.disabled,
+            value: palette.text,
+            selection: palette.base_lighter,
+        };
+
+        match status {
+            text_input::Status::Active => style,
+            text_input::Status::Hovered => text_input::Style {
+                border: Border {
+                    color: palette.base_lighter,
+                    ..style.border
+                },
+                ..style
+            },
+            text_input::Status::Focused => text_input::Style {
+                border: Border {
+                    color: palette.accent_secondary,
+                    ..style.border
+                },
+                ..style
+            },
+            text_input::Status::Disabled => text_input::Style {
+                value: palette.disabled,
+                ..style
+            },
+        }
+    }
+}
+
+impl pick_list::Catalog for Theme {
+    type Class<'a> = ();
+
+    fn default<'a>() -> <Self as pick_list::Catalog>::Class<'a> {}
+
+    fn style(
+        &self,
+        _class: &<Self as pick_list::Catalog>::Class<'_>,
+        status: pick_list::Status,
+    ) -> pick_list::Style {
+        let palette = self.palette();
+
+        pick_list::Style {
+            text_color: palette.text,
+            placeholder_color: palette.disabled,
+            handle_color: palette.text,
+            background: palette.background.into(),
+            border: Border {
+                radius: 2.0.into(),
+                width: 1.0,
+                color: match status {
+                    pick_list::Status::Active => palette.base,
+                    pick_list::Status::Hovered => palette.base_lighter,
+                    pick_list::Status::Opened => palette.accent_secondary,
+                },
+            },
+        }
+    }
+}
+
+impl menu::Catalog for Theme {
+    type Class<'a> = ();
+
+    fn default<'a>() -> <Self as menu::Catalog>::Class<'a> {}
+
+    fn style(&self, _class: &<Self as menu::Catalog>::Class<'_>) -> menu::Style {
+        let palette = self.palette();
+
+        menu::Style {
+            background: palette.base_darkest.into(),
+            border: Border {
+                radius: 2.0.into(),
+                width: 1.0,
+                color: palette.base,
+            },
+            text_color: palette.text,
+            selected_text_color: palette.text,
+            selected_background: palette.base_darker.into(),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::editor;
+use crate::theme::{ContainerClass, TextClass, Theme};
+use iced::widget::{button, column, container, opaque, row, text};
+use iced::{Alignment, Length};
+
+type Element<'a, Message> = iced::Element<'a, Message, Theme>;
+
+/// A notification shown over the app until it's dismissed.
+#[derive(Clone, Debug)]
+pub struct Toast {
+    pub level: Level,
+    pub title: String,
+    pub description: String,
+    /// Sent to the editor to try the failed action again.
+    pub retry: Option<editor::Message>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    /// Something was recovered from, but the user should know about it.
+    Warning,
+    Error,
+}
+
+impl Toast {
+    pub fn error(title: impl Into<String>, description: impl ToString) -> Self {
+        Self {
+            level: Level::Error,
+            title: title.into(),
+            description: description.to_string(),
+            retry: None,
+        }
+    }
+
+    pub fn warning(title: impl Into<String>, description: impl ToString) -> Self {
+        Self {
+            level: Level::Warning,
+            ..Self::error(title, description)
+        }
+    }
+
+    pub fn retry(self, retry: editor::Message) -> Self {
+        Self {
+            retry: Some(retry),
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    Dismiss(usize),
+    Retry(usize),
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<(usize, Toast)>,
+    next_id: usize,
+}
+
+impl Toasts {
+    pub fn push(&mut self, toast: Toast) {
+        // Don't stack up the same failure over and over
+        self.toasts.retain(|(_, shown)| {
+            shown.title != toast.title || shown.description != toast.description
+        });
+
+        self.toasts.push((self.next_id, toast));
+        self.next_id += 1;
+    }
+
+    pub fn dismiss(&mut self, id: usize) -> Option<Toast> {
+        let index = self.toasts.iter().position(|(toast, _)| *toast == id)?;
+
+        Some(self.toasts.remove(index).1)
+    }
+
+    pub fn view(&'_ self) -> Option<Element<'_, Message>> {
+        if self.toasts.is_empty() {
+            return None;
+        }
+
+        let toasts = column(self.toasts.iter().map(|(id, toast)| {
+            let mut actions = row![].spacing(10);
+
+            if toast.retry.is_some() {
+                actions = actions.push(button(text("Retry")).on_press(Message::Retry(*id)));
+            }
+
+            actions = actions.push(button(text("Dismiss")).on_press(Message::Dismiss(*id)));
+
+            // Clicks on a toast shouldn't reach the editor underneath it
+            opaque(
+                container(
+                    column![
+                        text(&toast.title).class(match toast.level {
+                            Level::Warning => TextClass::Warning,
+                            Level::Error => TextClass::Error,
+                        }),
+                        text(&toast.description),
+                        container(actions).align_right(Length::Fill),
+                    ]
+                    .spacing(10),
+                )
+                .width(Length::Fill)
+                .padding(15)
+                .class(ContainerClass::Dialog),
+            )
+        }))
+        .spacing(10)
+        .width(360)
+        .align_x(Alignment::End);
+
+        Some(
+            container(toasts)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_right(Length::Fill)
+                .align_bottom(Length::Fill)
+                .padding(20)
+                .into(),
+        )
+    }
+}
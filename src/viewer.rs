@@ -1,81 +1,190 @@
+mod buffers;
+mod channels;
 mod pipeline;
+mod playback;
 mod primitive;
 mod uniforms;
 
 use crate::theme::Theme;
+pub use crate::viewer::channels::{Channel, Filter, Image, Wrap};
+pub use crate::viewer::playback::Change as PlaybackChange;
+use crate::viewer::playback::Playback;
 use crate::viewer::primitive::Primitive;
-use crate::viewer::uniforms::Uniforms;
-use crate::{FragmentShader, Message};
+use crate::viewer::uniforms::{Click, Frames, Uniforms};
+use crate::{CHANNELS, Message, Shader};
 use iced::advanced::Shell;
 use iced::event::Status;
-use iced::widget::shader::Event;
-use iced::widget::{Shader, pane_grid};
+use iced::widget::shader::{Event, wgpu};
+use iced::widget::{pane_grid, shader, stack};
 use iced::window::RedrawRequest;
 use iced::{Length, Point, Rectangle, mouse};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Instant;
 
 pub struct Viewer {
-    start: Instant,
-    pub last_valid_shader: Arc<FragmentShader>,
+    pub playback: Playback,
+    pub last_valid_shader: Shader,
     pub version: usize,
+    /// The contents of the shader's params uniform, empty if it doesn't declare one.
+    pub params: Vec<u8>,
+    /// Bumped to clear the buffers of multi-pass shaders.
+    pub generation: usize,
+    /// Filled in by the primitive once it has seen the device.
+    device: Arc<OnceLock<Device>>,
+    /// The last shader wgpu rejected, until it's reported.
+    failure: Arc<Mutex<Option<PipelineError>>>,
+}
+
+/// What the viewer's device was created with, which is all shaders get to use. iced asks for
+/// none of the adapter's optional features, so `features` is empty on every adapter.
+#[derive(Clone, Debug)]
+pub struct Device {
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+}
+
+/// A shader naga accepted but wgpu couldn't build a pipeline from.
+#[derive(Clone, Debug)]
+pub struct PipelineError {
+    pub shader: Shader,
+    pub message: String,
 }
 
 impl Default for Viewer {
     fn default() -> Self {
         Self {
-            start: Instant::now(),
-            last_valid_shader: Arc::new(
-                include_str!("viewer/shaders/default_frag.wgsl").to_string(),
-            ),
+            playback: Playback::default(),
+            last_valid_shader: Shader::bundled(include_str!("viewer/shaders/default_frag.wgsl")),
             version: 0,
+            params: Vec::new(),
+            generation: 0,
+            device: Arc::default(),
+            failure: Arc::default(),
         }
     }
 }
 
+/// Declarations put in front of every shader, along with the buffers for multi-pass ones and the
+/// channels that have a texture.
+pub fn prelude(has_buffers: bool, channels: [bool; CHANNELS]) -> String {
+    let mut prelude = include_str!("viewer/shaders/uniforms.wgsl").to_string();
+
+    if has_buffers {
+        prelude.push('\n');
+        prelude.push_str(include_str!("viewer/shaders/buffers.wgsl"));
+    }
+
+    if channels.contains(&true) {
+        prelude.push('\n');
+        prelude.push_str(&channels::prelude(channels));
+    }
+
+    prelude
+}
+
 impl Viewer {
     pub fn content(&'_ self) -> pane_grid::Content<'_, Message, Theme> {
-        Shader::new(self)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .into()
+        let viewer = shader(self).width(Length::Fill).height(Length::Fill);
+
+        stack![viewer, self.playback.view()].into()
     }
+
+    pub fn update_playback(&mut self, change: PlaybackChange) {
+        self.playback.apply(change, Instant::now());
+
+        // Start the buffers over along with the animation
+        if let PlaybackChange::Rewind = change {
+            self.generation += 1;
+        }
+    }
+}
+
+/// What the viewer keeps track of between frames.
+#[derive(Default)]
+pub struct State {
+    /// Whether the device was reported.
+    is_reported: bool,
+    frames: Frames,
+    click: Click,
+    /// Set by a press until the next frame picks it up.
+    was_pressed: bool,
 }
 
-impl<Message> iced::widget::shader::Program<Message> for Viewer {
-    type State = ();
+impl iced::widget::shader::Program<Message> for Viewer {
+    type State = State;
     type Primitive = Primitive;
 
     fn update(
         &self,
-        _state: &mut Self::State,
-        _event: Event,
-        _bounds: Rectangle,
-        _cursor: mouse::Cursor,
+        state: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
         shell: &mut Shell<'_, Message>,
     ) -> (Status, Option<Message>) {
         shell.request_redraw(RedrawRequest::NextFrame);
 
+        match event {
+            Event::RedrawRequested(now) => {
+                state.frames.advance(self.playback.time(now), self.generation);
+                state.click.is_new = std::mem::take(&mut state.was_pressed);
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let Some(position) = cursor.position_over(bounds) {
+                    state.click = Click {
+                        position,
+                        pressed_at: position,
+                        is_down: true,
+                        is_new: false,
+                    };
+                    state.was_pressed = true;
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) if state.click.is_down => {
+                state.click.position = position;
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                state.click.is_down = false;
+            }
+            _ => {}
+        }
+
+        // The device is only reachable while rendering, so pass it on from the first update after
+        if !state.is_reported && let Some(device) = self.device.get() {
+            state.is_reported = true;
+
+            return (Status::Ignored, Some(Message::DeviceReported(device.clone())));
+        }
+
+        if let Some(error) = self.failure.lock().unwrap().take() {
+            return (Status::Ignored, Some(Message::PipelineFailed(error)));
+        }
+
         (Status::Ignored, None)
     }
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         cursor: mouse::Cursor,
         bounds: Rectangle,
     ) -> Self::Primitive {
         Primitive {
             uniforms: Uniforms {
-                time: Instant::now() - self.start,
                 mouse: match cursor {
                     mouse::Cursor::Available(pt) => pt,
                     mouse::Cursor::Unavailable => Point::new(-1.0, -1.0),
                 },
                 bounds,
+                frames: state.frames,
+                click: state.click,
             },
             shader: self.last_valid_shader.clone(),
             version: self.version,
+            params: self.params.clone(),
+            generation: self.generation,
+            device: self.device.clone(),
+            failure: self.failure.clone(),
         }
     }
 }
@@ -0,0 +1,132 @@
+use crate::BUFFERS;
+use iced::widget::shader::wgpu;
+
+/// What buffer passes render into. Each buffer has two textures, one written this frame while the
+/// other still holds the previous frame for every pass to read.
+pub struct Buffers {
+    pub size: wgpu::Extent3d,
+    /// Bumped to start over from empty buffers.
+    pub generation: usize,
+    frame: usize,
+    textures: Vec<[wgpu::TextureView; 2]>,
+    /// Reads every buffer's first or second texture.
+    bind_groups: [wgpu::BindGroup; 2],
+}
+
+impl Buffers {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    /// A texture for each buffer and the sampler they share.
+    pub const BINDINGS: u32 = BUFFERS as u32 + 1;
+
+    pub fn layout_entries() -> Vec<wgpu::BindGroupLayoutEntry> {
+        let texture = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        let sampler = wgpu::BindGroupLayoutEntry {
+            binding: BUFFERS as u32,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+
+        (0..BUFFERS as u32)
+            .map(texture)
+            .chain(std::iter::once(sampler))
+            .collect()
+    }
+
+    /// Freshly created textures are cleared, so this also resets the buffers. `channels` are
+    /// bound alongside them, since they share a group.
+    pub fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        size: wgpu::Extent3d,
+        generation: usize,
+        channels: &[wgpu::BindGroupEntry],
+    ) -> Self {
+        let texture = || {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("halo.buffers.texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: Self::FORMAT,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        let textures: Vec<_> = (0..BUFFERS).map(|_| [texture(), texture()]).collect();
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("halo.buffers.sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group = |side: usize| {
+            let entries: Vec<_> = textures
+                .iter()
+                .enumerate()
+                .map(|(i, views)| wgpu::BindGroupEntry {
+                    binding: i as u32,
+                    resource: wgpu::BindingResource::TextureView(&views[side]),
+                })
+                .chain(std::iter::once(wgpu::BindGroupEntry {
+                    binding: BUFFERS as u32,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                }))
+                .chain(channels.iter().cloned())
+                .collect();
+
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("halo.buffers.bind_group"),
+                layout,
+                entries: &entries,
+            })
+        };
+
+        let bind_groups = [bind_group(0), bind_group(1)];
+
+        Self {
+            size,
+            generation,
+            frame: 0,
+            textures,
+            bind_groups,
+        }
+    }
+
+    /// Swaps which textures are written and which are read.
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Where `buffer` is rendered to this frame.
+    pub fn target(&self, buffer: usize) -> &wgpu::TextureView {
+        &self.textures[buffer][self.frame % 2]
+    }
+
+    /// Every buffer as it was at the end of the previous frame.
+    pub fn previous(&self) -> &wgpu::BindGroup {
+        &self.bind_groups[(self.frame + 1) % 2]
+    }
+
+    /// Every buffer as it was rendered this frame.
+    pub fn current(&self) -> &wgpu::BindGroup {
+        &self.bind_groups[self.frame % 2]
+    }
+}
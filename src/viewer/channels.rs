@@ -0,0 +1,374 @@
+use crate::CHANNELS;
+use crate::viewer::buffers::Buffers;
+use iced::widget::shader::wgpu;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// A texture for the shader to sample, along with how to sample it.
+#[derive(Clone, Debug)]
+pub struct Channel {
+    /// Missing while the image is still loading, the channel reads as black until then.
+    pub image: Option<Arc<Image>>,
+    pub filter: Filter,
+    pub wrap: Wrap,
+}
+
+/// An image decoded from disk, ready to upload.
+#[derive(Debug)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Pixels,
+}
+
+#[derive(Debug)]
+pub enum Pixels {
+    /// From PNG and JPEG files, which are treated as sRGB.
+    Rgba8(Vec<u8>),
+    /// From HDR files, as half floats so values above one survive.
+    Rgba16Float(Vec<u16>),
+}
+
+impl Image {
+    pub fn open(path: &Path) -> Result<Self, image::ImageError> {
+        let image = image::open(path)?;
+        let (width, height) = (image.width(), image.height());
+
+        let pixels = match image {
+            image::DynamicImage::ImageRgb32F(_) | image::DynamicImage::ImageRgba32F(_) => {
+                Pixels::Rgba16Float(
+                    image
+                        .into_rgba32f()
+                        .into_raw()
+                        .into_iter()
+                        .map(|value| half::f16::from_f32(value).to_bits())
+                        .collect(),
+                )
+            }
+            image => Pixels::Rgba8(image.into_rgba8().into_raw()),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    fn format(&self) -> wgpu::TextureFormat {
+        match self.pixels {
+            Pixels::Rgba8(_) => wgpu::TextureFormat::Rgba8UnormSrgb,
+            Pixels::Rgba16Float(_) => wgpu::TextureFormat::Rgba16Float,
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match &self.pixels {
+            Pixels::Rgba8(pixels) => pixels,
+            Pixels::Rgba16Float(pixels) => bytemuck::cast_slice(pixels),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Filter {
+    #[default]
+    Linear,
+    Nearest,
+}
+
+impl Filter {
+    pub const ALL: [Filter; 2] = [Filter::Linear, Filter::Nearest];
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Filter::Linear => "Linear",
+            Filter::Nearest => "Nearest",
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Wrap {
+    #[default]
+    Repeat,
+    Mirror,
+    Clamp,
+}
+
+impl Wrap {
+    pub const ALL: [Wrap; 3] = [Wrap::Repeat, Wrap::Mirror, Wrap::Clamp];
+}
+
+impl Display for Wrap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Wrap::Repeat => "Repeat",
+            Wrap::Mirror => "Mirror",
+            Wrap::Clamp => "Clamp",
+        })
+    }
+}
+
+/// Declares `channel0` to `channel3` and their samplers, for the channels that are used.
+pub fn prelude(used: [bool; CHANNELS]) -> String {
+    let mut prelude = String::from("// Textures picked in the Pipeline pane.\n");
+
+    for channel in (0..CHANNELS).filter(|&channel| used[channel]) {
+        let (texture, sampler) = bindings(channel);
+
+        let _ = writeln!(
+            prelude,
+            "@group(1) @binding({texture}) var channel{channel}: texture_2d<f32>;\n\
+             @group(1) @binding({sampler}) var channel{channel}_sampler: sampler;"
+        );
+    }
+
+    prelude
+}
+
+/// Channels share group 1 with the buffers, since iced creates its device with only two bind
+/// groups. Each channel keeps its bindings, after the buffers', whether or not the buffers or the
+/// other channels are used.
+fn bindings(channel: usize) -> (u32, u32) {
+    let texture = Buffers::BINDINGS + channel as u32 * 2;
+
+    (texture, texture + 1)
+}
+
+pub fn layout_entries(used: [bool; CHANNELS]) -> Vec<wgpu::BindGroupLayoutEntry> {
+    (0..CHANNELS)
+        .filter(|&channel| used[channel])
+        .flat_map(|channel| {
+            let (texture, sampler) = bindings(channel);
+
+            [
+                wgpu::BindGroupLayoutEntry {
+                    binding: texture,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: sampler,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ]
+        })
+        .collect()
+}
+
+/// What a shader's channels are bound to, kept around for the bind groups the buffers recreate
+/// when they're resized.
+pub struct Bindings {
+    samplers: Vec<(usize, wgpu::Sampler)>,
+    views: [Arc<wgpu::TextureView>; CHANNELS],
+}
+
+impl Bindings {
+    pub fn new(
+        device: &wgpu::Device,
+        channels: &[Option<Channel>; CHANNELS],
+        views: &[Arc<wgpu::TextureView>; CHANNELS],
+    ) -> Self {
+        let samplers = channels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, channel)| Some((i, channel.as_ref()?)))
+            .map(|(i, channel)| {
+                let filter = match channel.filter {
+                    Filter::Linear => wgpu::FilterMode::Linear,
+                    Filter::Nearest => wgpu::FilterMode::Nearest,
+                };
+                let wrap = match channel.wrap {
+                    Wrap::Repeat => wgpu::AddressMode::Repeat,
+                    Wrap::Mirror => wgpu::AddressMode::MirrorRepeat,
+                    Wrap::Clamp => wgpu::AddressMode::ClampToEdge,
+                };
+
+                let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+                    label: Some("halo.channels.sampler"),
+                    address_mode_u: wrap,
+                    address_mode_v: wrap,
+                    mag_filter: filter,
+                    min_filter: filter,
+                    ..Default::default()
+                });
+
+                (i, sampler)
+            })
+            .collect();
+
+        Self {
+            samplers,
+            views: views.clone(),
+        }
+    }
+
+    pub fn entries(&self) -> Vec<wgpu::BindGroupEntry<'_>> {
+        self.samplers
+            .iter()
+            .flat_map(|(i, sampler)| {
+                let (texture, sampler_binding) = bindings(*i);
+
+                [
+                    wgpu::BindGroupEntry {
+                        binding: texture,
+                        resource: wgpu::BindingResource::TextureView(&self.views[*i]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: sampler_binding,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Channel images on the GPU, kept apart from the pipeline so editing a shader doesn't upload
+/// its textures again.
+pub struct Textures {
+    uploaded: Vec<(Arc<Image>, Arc<wgpu::TextureView>)>,
+    /// Read by channels without an image yet.
+    black: Arc<wgpu::TextureView>,
+}
+
+impl Textures {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let black = Image {
+            width: 1,
+            height: 1,
+            pixels: Pixels::Rgba8(vec![0, 0, 0, 255]),
+        };
+
+        Self {
+            uploaded: Vec::new(),
+            black: upload(device, queue, &black),
+        }
+    }
+
+    /// A view for each channel, uploading new images and dropping the ones no channel uses
+    /// anymore. Fails without uploading anything if an image is too large for the device.
+    pub fn views(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        channels: &[Option<Channel>; CHANNELS],
+    ) -> Result<[Arc<wgpu::TextureView>; CHANNELS], TooLarge> {
+        let max = device.limits().max_texture_dimension_2d;
+
+        if let Some((channel, image)) = channels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, channel)| Some((i, channel.as_ref()?.image.as_ref()?)))
+            .find(|(_, image)| image.width > max || image.height > max)
+        {
+            return Err(TooLarge {
+                channel,
+                width: image.width,
+                height: image.height,
+                max,
+            });
+        }
+
+        let images: Vec<_> = channels
+            .iter()
+            .flatten()
+            .filter_map(|channel| channel.image.as_ref())
+            .collect();
+
+        self.uploaded
+            .retain(|(uploaded, _)| images.iter().any(|image| Arc::ptr_eq(image, uploaded)));
+
+        for image in images {
+            if !self
+                .uploaded
+                .iter()
+                .any(|(uploaded, _)| Arc::ptr_eq(image, uploaded))
+            {
+                self.uploaded
+                    .push((image.clone(), upload(device, queue, image)));
+            }
+        }
+
+        Ok(std::array::from_fn(|i| {
+            channels[i]
+                .as_ref()
+                .and_then(|channel| channel.image.as_ref())
+                .and_then(|image| {
+                    self.uploaded
+                        .iter()
+                        .find(|(uploaded, _)| Arc::ptr_eq(image, uploaded))
+                })
+                .map_or_else(|| self.black.clone(), |(_, view)| view.clone())
+        }))
+    }
+}
+
+/// A channel's image is wider or taller than the device allows textures to be.
+#[derive(Clone, Copy, Debug)]
+pub struct TooLarge {
+    channel: usize,
+    width: u32,
+    height: u32,
+    max: u32,
+}
+
+impl Display for TooLarge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The image in channel{} is {}×{}, but this device only allows textures up to {}×{}",
+            self.channel, self.width, self.height, self.max, self.max
+        )
+    }
+}
+
+fn upload(device: &wgpu::Device, queue: &wgpu::Queue, image: &Image) -> Arc<wgpu::TextureView> {
+    let format = image.format();
+    let size = wgpu::Extent3d {
+        width: image.width,
+        height: image.height,
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("halo.channels.texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        image.bytes(),
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(image.width * format.block_copy_size(None).unwrap_or(4)),
+            rows_per_image: Some(image.height),
+        },
+        size,
+    );
+
+    Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
@@ -1,21 +1,64 @@
-use crate::viewer::uniforms;
+use crate::viewer::buffers::Buffers;
+use crate::viewer::{channels, prelude, uniforms};
+use crate::{CHANNELS, Shader};
 use iced::Rectangle;
 use iced::widget::shader::wgpu;
 use std::borrow::Cow;
+use std::ops::Range;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
 
 pub struct Pipeline {
     pub version: usize,
     uniforms: wgpu::Buffer,
+    /// Set when the shader declares params.
+    params: Option<wgpu::Buffer>,
     bind_group: wgpu::BindGroup,
     pipeline: wgpu::RenderPipeline,
+    vertices: Range<u32>,
+    instances: Range<u32>,
+    /// The pipeline for each buffer pass along with the buffer it renders to, in order.
+    buffer_passes: Vec<(usize, wgpu::RenderPipeline)>,
+    /// Group 1, set for multi-pass shaders and ones that sample channels.
+    textures_layout: Option<wgpu::BindGroupLayout>,
+    /// Created once the size to render at is known.
+    buffers: Option<Buffers>,
+    /// Set when any channel has a texture.
+    channels: Option<channels::Bindings>,
+    /// Binds the channels of single pass shaders, the buffers' bind groups do for the others.
+    channels_bind_group: Option<wgpu::BindGroup>,
 }
 
 impl Pipeline {
+    /// Builds the pipeline for `shader`, catching what wgpu rejects instead of letting it reach
+    /// the device's error handler, which panics. `channel_views` are what each channel samples.
     pub fn new(
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
-        shader: &str,
+        shader: &Shader,
         version: usize,
+        channel_views: &[Arc<wgpu::TextureView>; CHANNELS],
+    ) -> Result<Self, wgpu::Error> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let pipeline = Self::create(device, format, shader, version, channel_views);
+
+        // wgpu-core, which every native backend goes through, pops error scopes into an already
+        // resolved future, so there's no need for an executor. Only the browser's WebGPU can be
+        // pending, and Halo doesn't run there.
+        match pin!(device.pop_error_scope()).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(Some(error)) => Err(error),
+            Poll::Ready(None) | Poll::Pending => Ok(pipeline),
+        }
+    }
+
+    fn create(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        shader: &Shader,
+        version: usize,
+        channel_views: &[Arc<wgpu::TextureView>; CHANNELS],
     ) -> Self {
         let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("halo.pipeline.uniforms"),
@@ -24,10 +67,21 @@ impl Pipeline {
             mapped_at_creation: false,
         });
 
-        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("halo.pipeline.uniform_bind_group_layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
+        let params = shader.params_size.map(|size| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("halo.pipeline.params"),
+                size,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        // The shader's params sit next to the built-in uniforms
+        let buffers: Vec<_> = std::iter::once(&uniforms).chain(params.as_ref()).collect();
+
+        let layout_entries: Vec<_> = (0..buffers.len() as u32)
+            .map(|binding| wgpu::BindGroupLayoutEntry {
+                binding,
                 visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
@@ -35,46 +89,98 @@ impl Pipeline {
                     min_binding_size: None,
                 },
                 count: None,
-            }],
+            })
+            .collect();
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("halo.pipeline.uniform_bind_group_layout"),
+            entries: &layout_entries,
         });
 
+        let entries: Vec<_> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: wgpu::BindingResource::Buffer(buffer.as_entire_buffer_binding()),
+            })
+            .collect();
+
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("halo.pipeline.uniform_bind_group"),
             layout: &layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::Buffer(uniforms.as_entire_buffer_binding()),
-            }],
+            entries: &entries,
         });
 
+        let channels_used = shader.channels_used();
+
+        // Buffers and channels share a group, iced only gives the device two
+        let textures_entries: Vec<_> = shader
+            .has_buffers()
+            .then(Buffers::layout_entries)
+            .into_iter()
+            .flatten()
+            .chain(channels::layout_entries(channels_used))
+            .collect();
+
+        let textures_layout = (!textures_entries.is_empty()).then(|| {
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("halo.pipeline.textures_bind_group_layout"),
+                entries: &textures_entries,
+            })
+        });
+
+        let bind_group_layouts: Vec<_> = std::iter::once(&layout)
+            .chain(textures_layout.as_ref())
+            .collect();
+
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("halo.pipeline.layout"),
-            bind_group_layouts: &[&layout],
+            bind_group_layouts: &bind_group_layouts,
             push_constant_ranges: &[],
         });
 
-        let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("halo.pipeline.shader"),
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
-                include_str!("shaders/uniforms.wgsl"),
-                "\n",
-                include_str!("shaders/vertex_shader.wgsl"),
-            ))),
-        });
-
         let fragment_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("halo.pipeline.fragment_shader"),
             source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(
-                format!("{}\n{}", include_str!("shaders/uniforms.wgsl"), shader).as_str(),
+                format!(
+                    "{}\n{}",
+                    prelude(shader.has_buffers(), channels_used),
+                    shader.source
+                )
+                .as_str(),
             )),
         });
 
+        // A custom vertex stage lives in the same module as the fragment stage
+        let quad;
+        let (vertex_shader, vertex_entry_point, vertices, instances) = match &shader.vertex {
+            Some(vertex) => (
+                &fragment_shader,
+                vertex.entry_point.as_str(),
+                0..vertex.vertex_count,
+                0..vertex.instance_count,
+            ),
+            None => {
+                quad = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("halo.pipeline.shader"),
+                    source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(concat!(
+                        include_str!("shaders/uniforms.wgsl"),
+                        "\n",
+                        include_str!("shaders/vertex_shader.wgsl"),
+                    ))),
+                });
+
+                (&quad, "vs_main", 0..6, 0..1)
+            }
+        };
+
         let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("halo.pipeline.pipeline"),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &vertex_shader,
-                entry_point: "vs_main",
+                module: vertex_shader,
+                entry_point: vertex_entry_point,
                 buffers: &[],
             },
             primitive: wgpu::PrimitiveState::default(),
@@ -82,7 +188,7 @@ impl Pipeline {
             multisample: Default::default(),
             fragment: Some(wgpu::FragmentState {
                 module: &fragment_shader,
-                entry_point: "fs_main",
+                entry_point: &shader.entry_point,
                 targets: &[Some(wgpu::ColorTargetState {
                     format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -92,16 +198,126 @@ impl Pipeline {
             multiview: None,
         });
 
+        let buffer_vertex_shader = shader.has_buffers().then(|| {
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("halo.pipeline.buffer_vertex_shader"),
+                source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!(
+                    "shaders/buffer_vertex.wgsl"
+                ))),
+            })
+        });
+
+        let buffer_passes = shader
+            .buffers
+            .iter()
+            .enumerate()
+            .filter_map(|(buffer, entry_point)| {
+                Some((buffer, entry_point.as_ref()?, buffer_vertex_shader.as_ref()?))
+            })
+            .map(|(buffer, entry_point, buffer_vertex_shader)| {
+                let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("halo.pipeline.buffer_pipeline"),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: buffer_vertex_shader,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: Default::default(),
+                    fragment: Some(wgpu::FragmentState {
+                        module: &fragment_shader,
+                        entry_point,
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: Buffers::FORMAT,
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    multiview: None,
+                });
+
+                (buffer, pipeline)
+            })
+            .collect();
+
+        let channels = channels_used
+            .contains(&true)
+            .then(|| channels::Bindings::new(device, &shader.channels, channel_views));
+
+        let channels_bind_group = match (&textures_layout, &channels) {
+            (Some(layout), Some(channels)) if !shader.has_buffers() => {
+                Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("halo.pipeline.channels_bind_group"),
+                    layout,
+                    entries: &channels.entries(),
+                }))
+            }
+            _ => None,
+        };
+
         Self {
             version,
             uniforms,
+            params,
             bind_group,
             pipeline,
+            vertices,
+            instances,
+            buffer_passes,
+            textures_layout,
+            buffers: None,
+            channels,
+            channels_bind_group,
         }
     }
 
-    pub fn prepare(&mut self, queue: &wgpu::Queue, uniforms: &uniforms::Raw) {
+    /// `size` is what the buffers are rendered at, they start over from empty when it or
+    /// `generation` changes. They only move on to the next frame when time moves forwards, so they
+    /// hold still while playback is paused.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        uniforms: &uniforms::Raw,
+        params: &[u8],
+        size: wgpu::Extent3d,
+        generation: usize,
+    ) {
         queue.write_buffer(&self.uniforms, 0, bytemuck::bytes_of(uniforms));
+
+        // Params for another shader can show up while a failed pipeline leaves this one in place
+        if let Some(buffer) = &self.params
+            && buffer.size() == params.len() as u64
+        {
+            queue.write_buffer(buffer, 0, params);
+        }
+
+        let Some(layout) = self
+            .textures_layout
+            .as_ref()
+            .filter(|_| !self.buffer_passes.is_empty())
+        else {
+            return;
+        };
+
+        match &mut self.buffers {
+            Some(buffers) if buffers.size == size && buffers.generation == generation => {
+                if uniforms.delta_time > 0.0 {
+                    buffers.next_frame();
+                }
+            }
+            _ => {
+                let channels = self
+                    .channels
+                    .as_ref()
+                    .map(channels::Bindings::entries)
+                    .unwrap_or_default();
+
+                self.buffers = Some(Buffers::new(device, layout, size, generation, &channels));
+            }
+        }
     }
 
     pub fn render(
@@ -110,6 +326,30 @@ impl Pipeline {
         target: &wgpu::TextureView,
         bounds: &Rectangle<u32>,
     ) {
+        if let Some(buffers) = &self.buffers {
+            for (buffer, pipeline) in &self.buffer_passes {
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("halo.buffer_pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: buffers.target(*buffer),
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &self.bind_group, &[]);
+                pass.set_bind_group(1, buffers.previous(), &[]);
+                pass.draw(0..3, 0..1);
+            }
+        }
+
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("halo.render_pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -129,6 +369,13 @@ impl Pipeline {
 
         pass.set_pipeline(&self.pipeline);
         pass.set_bind_group(0, &self.bind_group, &[]);
-        pass.draw(0..6, 0..1);
+
+        if let Some(buffers) = &self.buffers {
+            pass.set_bind_group(1, buffers.current(), &[]);
+        } else if let Some(channels) = &self.channels_bind_group {
+            pass.set_bind_group(1, channels, &[]);
+        }
+
+        pass.draw(self.vertices.clone(), self.instances.clone());
     }
 }
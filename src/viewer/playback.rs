@@ -0,0 +1,157 @@
+use crate::Message;
+use crate::theme::{ContainerClass, Theme};
+use iced::widget::{button, container, pick_list, row, slider, text, tooltip};
+use iced::{Alignment, Element, Font, Length};
+use lucide_icons::Icon;
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+/// How far a step moves, one frame at 60 fps.
+const STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// The timeline grows by this much at a time as the animation plays past its end.
+const TIMELINE_LENGTH: f32 = 60.0;
+
+/// Drives the `time` uniform, which only moves while playing.
+#[derive(Debug)]
+pub struct Playback {
+    /// Where the animation was when it last started playing or was moved.
+    time: Duration,
+    /// When it last started playing, unset while paused.
+    playing_since: Option<Instant>,
+    speed: Speed,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Change {
+    TogglePlaying,
+    /// Pauses and moves a frame forwards or backwards.
+    Step(bool),
+    Rewind,
+    /// Jumps to a time in seconds.
+    Seek(f32),
+    Speed(Speed),
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            time: Duration::ZERO,
+            playing_since: Some(Instant::now()),
+            speed: Speed(1.0),
+        }
+    }
+}
+
+impl Playback {
+    /// Where the animation is at `now`.
+    pub fn time(&self, now: Instant) -> Duration {
+        self.time
+            + self
+                .playing_since
+                .map_or(Duration::ZERO, |since| (now - since).mul_f32(self.speed.0))
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing_since.is_some()
+    }
+
+    pub fn apply(&mut self, change: Change, now: Instant) {
+        // Start over from where the animation is, so changes only affect what comes after
+        self.time = self.time(now);
+        self.playing_since = self.playing_since.map(|_| now);
+
+        match change {
+            Change::TogglePlaying => {
+                self.playing_since = match self.playing_since {
+                    Some(_) => None,
+                    None => Some(now),
+                };
+            }
+            Change::Step(is_forwards) => {
+                self.playing_since = None;
+                self.time = if is_forwards {
+                    self.time + STEP
+                } else {
+                    self.time.saturating_sub(STEP)
+                };
+            }
+            Change::Rewind => self.time = Duration::ZERO,
+            Change::Seek(seconds) => self.time = Duration::from_secs_f32(seconds.max(0.0)),
+            Change::Speed(speed) => self.speed = speed,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message, Theme> {
+        let time = self.time(Instant::now()).as_secs_f32();
+        let length = (time / TIMELINE_LENGTH).floor() * TIMELINE_LENGTH + TIMELINE_LENGTH;
+
+        let play = if self.is_playing() {
+            control(Icon::Pause, "Pause", Change::TogglePlaying)
+        } else {
+            control(Icon::Play, "Play", Change::TogglePlaying)
+        };
+
+        let timeline = slider(0.0..=length, time, |seconds| {
+            Message::Playback(Change::Seek(seconds))
+        })
+        .step(0.01)
+        .width(Length::Fill);
+
+        let speed = pick_list(Speed::ALL, Some(self.speed), |speed| {
+            Message::Playback(Change::Speed(speed))
+        });
+
+        let bar = row![
+            control(Icon::SkipBack, "Rewind", Change::Rewind),
+            control(Icon::StepBack, "Previous frame", Change::Step(false)),
+            play,
+            control(Icon::StepForward, "Next frame", Change::Step(true)),
+            timeline,
+            text(format!("{time:.2}s")).width(70),
+            speed,
+        ]
+        .spacing(10)
+        .padding([5, 10])
+        .align_y(Alignment::Center);
+
+        container(container(bar).class(ContainerClass::Controls))
+            .height(Length::Fill)
+            .align_bottom(Length::Fill)
+            .into()
+    }
+}
+
+fn control<'a>(icon: Icon, label: &'a str, change: Change) -> Element<'a, Message, Theme> {
+    let icon = text(icon.unicode().to_string()).font(Font::with_name("lucide"));
+
+    tooltip(
+        button(container(icon).center_x(30)).on_press(Message::Playback(change)),
+        label,
+        tooltip::Position::Top,
+    )
+    .padding(10)
+    .class(ContainerClass::Tooltip)
+    .into()
+}
+
+/// How fast the animation plays relative to real time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Speed(f32);
+
+impl Speed {
+    pub const ALL: [Speed; 6] = [
+        Speed(0.1),
+        Speed(0.25),
+        Speed(0.5),
+        Speed(1.0),
+        Speed(2.0),
+        Speed(4.0),
+    ];
+}
+
+impl Display for Speed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}×", self.0)
+    }
+}
@@ -1,15 +1,23 @@
+use crate::Shader;
+use crate::viewer;
+use crate::viewer::PipelineError;
+use crate::viewer::channels::Textures;
 use crate::viewer::pipeline::Pipeline;
 use crate::viewer::uniforms::Uniforms;
 use iced::Rectangle;
 use iced::advanced::graphics::Viewport;
 use iced::widget::shader::{Storage, wgpu};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 
 #[derive(Debug)]
 pub struct Primitive {
     pub uniforms: Uniforms,
-    pub shader: Arc<String>,
+    pub shader: Shader,
     pub version: usize,
+    pub params: Vec<u8>,
+    pub generation: usize,
+    pub device: Arc<OnceLock<viewer::Device>>,
+    pub failure: Arc<Mutex<Option<PipelineError>>>,
 }
 
 impl iced::widget::shader::Primitive for Primitive {
@@ -19,21 +27,79 @@ impl iced::widget::shader::Primitive for Primitive {
         queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         storage: &mut Storage,
-        _bounds: &Rectangle,
+        bounds: &Rectangle,
         viewport: &Viewport,
     ) {
+        self.device.get_or_init(|| viewer::Device {
+            features: device.features(),
+            limits: device.limits(),
+        });
+
         let should_store = storage
             .get::<Pipeline>()
             .map(|pipeline| pipeline.version < self.version)
             .unwrap_or(true);
 
         if should_store {
-            storage.store(Pipeline::new(device, format, &self.shader, self.version));
+            if !storage.has::<Textures>() {
+                storage.store(Textures::new(device, queue));
+            }
+
+            let pipeline = storage
+                .get_mut::<Textures>()
+                .unwrap()
+                .views(device, queue, &self.shader.channels)
+                .map_err(|error| error.to_string())
+                .and_then(|channel_views| {
+                    Pipeline::new(device, format, &self.shader, self.version, &channel_views)
+                        .map_err(|error| error.to_string())
+                });
+
+            match pipeline {
+                Ok(pipeline) => storage.store(pipeline),
+                Err(message) => {
+                    *self.failure.lock().unwrap() = Some(PipelineError {
+                        shader: self.shader.clone(),
+                        message,
+                    });
+
+                    // Keep drawing the last shader that worked, and don't retry this one every frame
+                    if let Some(pipeline) = storage.get_mut::<Pipeline>() {
+                        pipeline.version = self.version;
+                    } else {
+                        let empty = Shader::bundled(include_str!("shaders/empty_frag.wgsl"));
+                        let channel_views = storage
+                            .get_mut::<Textures>()
+                            .unwrap()
+                            .views(device, queue, &empty.channels)
+                            .expect("Empty shader has no channel images");
+
+                        storage.store(
+                            Pipeline::new(device, format, &empty, self.version, &channel_views)
+                                .expect("Empty shader should always build"),
+                        );
+                    }
+                }
+            }
         }
 
         let pipeline = storage.get_mut::<Pipeline>().unwrap();
 
-        pipeline.prepare(queue, &self.uniforms.to_raw(viewport));
+        let scale_factor = viewport.scale_factor() as f32;
+        let size = wgpu::Extent3d {
+            width: ((bounds.width * scale_factor) as u32).max(1),
+            height: ((bounds.height * scale_factor) as u32).max(1),
+            depth_or_array_layers: 1,
+        };
+
+        pipeline.prepare(
+            device,
+            queue,
+            &self.uniforms.to_raw(viewport),
+            &self.params,
+            size,
+            self.generation,
+        );
     }
 
     fn render(
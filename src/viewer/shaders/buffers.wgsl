@@ -0,0 +1,6 @@
+// Buffer A to D as they were at the end of the previous frame. The Image pass sees this frame's.
+@group(1) @binding(0) var buffer_a: texture_2d<f32>;
+@group(1) @binding(1) var buffer_b: texture_2d<f32>;
+@group(1) @binding(2) var buffer_c: texture_2d<f32>;
+@group(1) @binding(3) var buffer_d: texture_2d<f32>;
+@group(1) @binding(4) var buffer_sampler: sampler;
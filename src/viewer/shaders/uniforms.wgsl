@@ -4,6 +4,19 @@ struct Uniforms {
     scale: vec2<f32>,
     mouse: vec2<f32>,
     time: f32,
+    // Seconds since the previous frame.
+    delta_time: f32,
+    // Like Shadertoy's iMouse, in the same pixels as @builtin(position): xy is where the left
+    // button is held, zw where it was pressed. z is negative once it's let go, w is only positive
+    // on the frame it was pressed.
+    click: vec4<f32>,
+    // Year, month from 0, day from 1 and seconds since midnight, like Shadertoy's iDate.
+    date: vec4<f32>,
+    // The size of the viewer in physical pixels, the same as scale.
+    resolution: vec2<f32>,
+    scale_factor: f32,
+    // Counts up from 0 on the first frame.
+    frame: u32,
 }
 
-@group(0) @binding(0) var<uniform> uniforms: Uniforms;
\ No newline at end of file
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
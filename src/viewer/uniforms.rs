@@ -1,33 +1,114 @@
 use iced::advanced::graphics::Viewport;
 use iced::{Point, Rectangle};
 use std::time::Duration;
+use time::OffsetDateTime;
 
 #[derive(Debug)]
 pub struct Uniforms {
-    pub time: Duration,
     pub mouse: Point,
     pub bounds: Rectangle,
+    pub frames: Frames,
+    pub click: Click,
 }
+
 impl Uniforms {
     pub fn to_raw(&self, viewport: &Viewport) -> Raw {
         let scale_factor = viewport.scale_factor() as f32;
         let transform_array: [f32; 16] = viewport.projection().into();
         let transform_matrix = glam::Mat4::from_cols_array(&transform_array);
+        let resolution = [
+            self.bounds.width * scale_factor,
+            self.bounds.height * scale_factor,
+        ];
 
         Raw {
             transform: transform_matrix,
             position: [self.bounds.x * scale_factor, self.bounds.y * scale_factor],
-            scale: [
-                self.bounds.width * scale_factor,
-                self.bounds.height * scale_factor,
-            ],
+            scale: resolution,
             mouse: self.mouse.into(),
-            time: self.time.as_secs_f32(),
-            _padding: 0.0,
+            time: self.frames.time.as_secs_f32(),
+            delta_time: self.frames.delta.as_secs_f32(),
+            click: self.click.to_raw(scale_factor),
+            date: date(),
+            resolution,
+            scale_factor,
+            frame: self.frames.index,
+        }
+    }
+}
+
+/// Counts the frames the viewer has drawn and where the animation was for each, so everything
+/// stands still while playback is paused.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Frames {
+    /// Zero on the first frame and whenever the viewer starts over.
+    pub index: u32,
+    pub time: Duration,
+    /// How far the animation moved since the last frame, never backwards.
+    pub delta: Duration,
+    /// The viewer's generation, which it bumps when rewinding or resetting the buffers.
+    generation: Option<usize>,
+}
+
+impl Frames {
+    pub fn advance(&mut self, time: Duration, generation: usize) {
+        let last = std::mem::replace(&mut self.time, time);
+        self.delta = Duration::ZERO;
+
+        if self.generation.replace(generation) != Some(generation) {
+            self.index = 0;
+        } else if time > last {
+            self.index = self.index.wrapping_add(1);
+            self.delta = time - last;
+        } else if time < last {
+            self.index = self.index.saturating_sub(1);
         }
     }
 }
 
+/// The left mouse button, the way Shadertoy's `iMouse` tracks it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Click {
+    /// Where the cursor is while the button is held, and where it was let go after.
+    pub position: Point,
+    /// Where the button was last pressed.
+    pub pressed_at: Point,
+    pub is_down: bool,
+    /// Set for the one frame after a press.
+    pub is_new: bool,
+}
+
+impl Click {
+    /// In physical pixels like `@builtin(position)`, with whether the button is down in the sign
+    /// of `z` and whether it was pressed this frame in the sign of `w`.
+    fn to_raw(self, scale_factor: f32) -> [f32; 4] {
+        let sign = |is_set: bool| if is_set { 1.0 } else { -1.0 };
+
+        [
+            self.position.x * scale_factor,
+            self.position.y * scale_factor,
+            self.pressed_at.x * scale_factor * sign(self.is_down),
+            self.pressed_at.y * scale_factor * sign(self.is_new),
+        ]
+    }
+}
+
+/// Year, month from zero, day of the month from one and seconds since midnight, like Shadertoy's
+/// `iDate`. In local time when the offset can be found, which it can't always be on Unix.
+fn date() -> [f32; 4] {
+    let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
+    let midnight = now.replace_time(time::Time::MIDNIGHT);
+
+    [
+        now.year() as f32,
+        u8::from(now.month()) as f32 - 1.0,
+        now.day() as f32,
+        (now - midnight).as_seconds_f32(),
+    ]
+}
+
+/// Laid out like `Uniforms` in uniforms.wgsl, where the fields after `time` fill what used to be
+/// padding and come after everything shaders already used.
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct Raw {
@@ -36,5 +117,10 @@ pub struct Raw {
     pub scale: [f32; 2],
     pub mouse: [f32; 2],
     pub time: f32,
-    pub _padding: f32,
+    pub delta_time: f32,
+    pub click: [f32; 4],
+    pub date: [f32; 4],
+    pub resolution: [f32; 2],
+    pub scale_factor: f32,
+    pub frame: u32,
 }